@@ -0,0 +1,95 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the timestamp in nanoseconds of the last transfer sent or received by the given
+    /// account, if any.
+    pub fn get_last_activity(&self, account_id: AccountId) -> Option<u64> {
+        self.last_activity.get(&account_id)
+    }
+
+    /// Returns the cumulative volume and count of the transfers made through the contract. The
+    /// volume counts the amounts sent, including the charged fees. The tokens refunded by
+    /// `ft_transfer_call` are not counted as another transfer.
+    pub fn get_transfer_stats(&self) -> (U128, u64) {
+        (self.total_transfer_volume.into(), self.total_transfer_count)
+    }
+}
+
+impl Contract {
+    /// Records the current block timestamp as the last activity of both transfer parties.
+    pub(crate) fn internal_record_activity(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) {
+        let now = env::block_timestamp();
+        self.last_activity.insert(sender_id, &now);
+        self.last_activity.insert(receiver_id, &now);
+    }
+
+    /// Adds a transfer of `amount` tokens to the transfer stats.
+    pub(crate) fn internal_record_transfer_stats(&mut self, amount: Balance) {
+        self.total_transfer_volume = self.total_transfer_volume.saturating_add(amount);
+        self.total_transfer_count += 1;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_transfer_records_activity() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        assert_eq!(contract.get_last_activity(accounts(1)), None);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_000)
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(contract.get_last_activity(accounts(1)), Some(1_000));
+        assert_eq!(contract.get_last_activity(accounts(2)), Some(1_000));
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(2_000).build());
+        contract.ft_transfer(accounts(2), 5.into(), None);
+        assert_eq!(contract.get_last_activity(accounts(1)), Some(2_000));
+        assert_eq!(contract.get_last_activity(accounts(2)), Some(2_000));
+    }
+
+    #[test]
+    fn test_transfer_stats() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        assert_eq!(contract.get_transfer_stats(), (U128(0), 0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        contract.ft_transfer_batch(vec![(accounts(1), 10.into()), (accounts(3), 20.into())], None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 5.into(), None);
+
+        assert_eq!(contract.get_transfer_stats(), (U128(135), 4));
+    }
+}
@@ -0,0 +1,124 @@
+use crate::internal::assert_not_contract;
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+
+/// The maximum number of entries in a single `airdrop` call, to keep it within the gas limit.
+const MAX_AIRDROP_ENTRIES: usize = 100;
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens from the owner to every account in `entries`, registering the accounts
+    /// that are not registered yet. The storage of the new accounts is paid from the attached
+    /// deposit and the unused part of the deposit is refunded to the owner.
+    /// Can only be called by the owner.
+    #[payable]
+    pub fn airdrop(&mut self, entries: Vec<(AccountId, U128)>) {
+        self.assert_owner();
+        assert!(!entries.is_empty(), "The airdrop should not be empty");
+        assert!(
+            entries.len() <= MAX_AIRDROP_ENTRIES,
+            "The airdrop can't contain more than {} entries",
+            MAX_AIRDROP_ENTRIES
+        );
+        let mut new_accounts: Vec<&AccountId> = vec![];
+        for (account_id, _) in entries.iter() {
+            if !new_accounts.contains(&account_id) && !self.token.accounts.contains_key(account_id)
+            {
+                new_accounts.push(account_id);
+            }
+        }
+        let storage_cost = self.storage_balance_bounds().min.0 * new_accounts.len() as u128;
+        let attached_deposit = env::attached_deposit();
+        assert!(
+            attached_deposit >= storage_cost,
+            "The attached deposit should be at least {} to register {} accounts",
+            storage_cost,
+            new_accounts.len()
+        );
+
+        let min_balance = self.storage_balance_bounds().min.0;
+        for account_id in new_accounts {
+            self.internal_register_paid_account(account_id, min_balance);
+            self.on_account_registered(account_id, min_balance);
+        }
+        let owner_id = self.owner_id.clone();
+        for (account_id, amount) in entries.iter() {
+            assert_ne!(account_id, &owner_id, "Can't airdrop to the owner");
+            assert_not_contract(account_id);
+            self.assert_not_frozen(account_id);
+            self.internal_settle_reflection(&owner_id);
+            self.internal_settle_reflection(account_id);
+            self.internal_withdraw(&owner_id, amount.0);
+            self.internal_deposit(account_id, amount.0);
+        }
+        let events: Vec<FtTransfer> = entries
+            .iter()
+            .map(|(account_id, amount)| FtTransfer {
+                old_owner_id: &owner_id,
+                new_owner_id: account_id,
+                amount,
+                memo: Some("Airdrop"),
+            })
+            .collect();
+        FtTransfer::emit_many(&events);
+
+        let refund = attached_deposit - storage_cost;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_airdrop_registers_new_accounts() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        let storage_cost = contract.storage_balance_bounds().min.0;
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(2 * storage_cost + 10)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.airdrop(vec![
+            (accounts(1), 10.into()),
+            (accounts(3), 20.into()),
+            (accounts(4), 30.into()),
+        ]);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 20);
+        assert_eq!(contract.ft_balance_of(accounts(4)).0, 30);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 60);
+        assert!(contract.storage_balance_of(accounts(4)).is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit should be at least")]
+    fn test_airdrop_insufficient_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let storage_cost = contract.storage_balance_bounds().min.0;
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(2 * storage_cost - 1)
+            .build());
+        contract.airdrop(vec![(accounts(3), 20.into()), (accounts(4), 30.into())]);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde_json::json;
+
+/// The maximum number of spenders an account can approve at the same time.
+const MAX_ALLOWANCES_PER_OWNER: usize = 16;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the amount `spender` is allowed to transfer on behalf of `owner`, zero once the
+    /// allowance expired.
+    pub fn allowance(&self, owner: AccountId, spender: AccountId) -> U128 {
+        self.internal_allowance(&owner, &spender).0.into()
+    }
+
+    /// Returns the amount `spender` is allowed to transfer on behalf of `owner` and the
+    /// timestamp in nanoseconds after which the allowance expires, if any.
+    pub fn allowance_with_expiry(
+        &self,
+        owner: AccountId,
+        spender: AccountId,
+    ) -> (U128, Option<U64>) {
+        let (amount, expiry_ns) = self.internal_allowance(&owner, &spender);
+        (amount.into(), expiry_ns.map(U64))
+    }
+
+    /// Allows `spender` to transfer up to `amount` tokens on behalf of the registered caller,
+    /// replacing the previous allowance. The allowance can't be spent once the block timestamp
+    /// passes `expiry_ns`, if given. An approval of zero removes the allowance. Requires at least
+    /// one yoctoNEAR attached, and enough to cover the storage of a new allowance. The rest of
+    /// the deposit is refunded.
+    #[payable]
+    pub fn approve(&mut self, spender: AccountId, amount: U128, expiry_ns: Option<U64>) {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let initial_storage_usage = env::storage_usage();
+        let owner = env::predecessor_account_id();
+        assert_ne!(owner, spender, "Can't approve to yourself");
+        let expiry_ns = expiry_ns.map(|expiry_ns| expiry_ns.0);
+        if let Some(expiry_ns) = expiry_ns {
+            assert!(env::block_timestamp() <= expiry_ns, "The expiry should be in the future");
+        }
+        self.internal_approve(&owner, &spender, amount.0, expiry_ns);
+        self.internal_charge_storage(initial_storage_usage);
+        log!("@{} approved @{} to spend {}", owner, spender, amount.0);
+    }
+
+    /// Transfers `amount` tokens from `owner` to `receiver` using the caller's allowance.
+    /// Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn transfer_from(
+        &mut self,
+        owner: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let spender = env::predecessor_account_id();
+        self.internal_spend_allowance(&owner, &spender, amount.0);
+        self.internal_ft_transfer(&owner, &receiver, amount.0, memo);
+    }
+
+    /// Transfers `amount` tokens from `owner` to `receiver` on behalf of a relayer, the caller,
+    /// who pays the gas and spends its allowance the same way as `transfer_from`. An
+    /// additional `sponsored_transfer` event attributes the transfer to the relayer. Requires
+    /// exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn sponsored_transfer(
+        &mut self,
+        owner: AccountId,
+        receiver: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let relayer = env::predecessor_account_id();
+        self.internal_spend_allowance(&owner, &relayer, amount.0);
+        let net_amount = self.internal_ft_transfer(&owner, &receiver, amount.0, memo);
+        self.emit_event(
+            "sponsored_transfer",
+            &[json!({
+                "owner_id": owner,
+                "receiver_id": receiver,
+                "relayer_id": relayer,
+                "amount": U128(net_amount),
+            })],
+        );
+    }
+}
+
+impl Contract {
+    /// Sets the allowance of `spender` on the tokens of the registered `owner`, limiting the
+    /// number of spenders of the owner.
+    pub(crate) fn internal_approve(
+        &mut self,
+        owner: &AccountId,
+        spender: &AccountId,
+        amount: Balance,
+        expiry_ns: Option<u64>,
+    ) {
+        scc_assert!(
+            self.token.accounts.contains_key(owner),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            owner
+        );
+        let spenders = self.allowance_spenders.get(owner).unwrap_or_default();
+        assert!(
+            amount == 0 || spenders.contains(spender) || spenders.len() < MAX_ALLOWANCES_PER_OWNER,
+            "An account can't approve more than {} spenders",
+            MAX_ALLOWANCES_PER_OWNER
+        );
+        self.internal_set_allowance(owner, spender, amount, expiry_ns);
+    }
+
+    pub(crate) fn internal_set_allowance(
+        &mut self,
+        owner: &AccountId,
+        spender: &AccountId,
+        amount: Balance,
+        expiry_ns: Option<u64>,
+    ) {
+        let key = (owner.clone(), spender.clone());
+        let mut spenders = self.allowance_spenders.get(owner).unwrap_or_default();
+        if amount == 0 {
+            self.allowances.remove(&key);
+            spenders.retain(|existing| existing != spender);
+        } else {
+            self.allowances.insert(&key, &(amount, expiry_ns));
+            if !spenders.contains(spender) {
+                spenders.push(spender.clone());
+            }
+        }
+        if spenders.is_empty() {
+            self.allowance_spenders.remove(owner);
+        } else {
+            self.allowance_spenders.insert(owner, &spenders);
+        }
+    }
+
+    /// Removes all the allowances `owner` granted.
+    pub(crate) fn internal_remove_allowances(&mut self, owner: &AccountId) {
+        for spender in self.allowance_spenders.remove(owner).unwrap_or_default() {
+            self.allowances.remove(&(owner.clone(), spender));
+        }
+    }
+
+    /// Returns the allowance of `spender` on `owner`'s tokens and its expiry, treating an
+    /// expired allowance as zero.
+    pub(crate) fn internal_allowance(
+        &self,
+        owner: &AccountId,
+        spender: &AccountId,
+    ) -> (Balance, Option<u64>) {
+        match self.allowances.get(&(owner.clone(), spender.clone())) {
+            Some((_, Some(expiry_ns))) if env::block_timestamp() > expiry_ns => (0, None),
+            Some(allowance) => allowance,
+            None => (0, None),
+        }
+    }
+
+    /// Decreases the allowance of `spender` on `owner`'s tokens by `amount`, keeping its expiry.
+    pub(crate) fn internal_spend_allowance(
+        &mut self,
+        owner: &AccountId,
+        spender: &AccountId,
+        amount: Balance,
+    ) {
+        let (allowance, expiry_ns) = self.internal_allowance(owner, spender);
+        scc_assert!(
+            allowance >= amount,
+            SccError::AllowanceExceeded,
+            "The allowance is not enough"
+        );
+        self.internal_set_allowance(owner, spender, allowance - amount, expiry_ns);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{
+        get_context, init_contract, register_account, STORAGE_DEPOSIT, TOTAL_SUPPLY,
+    };
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.approve(accounts(3), 100.into(), None);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(3)).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_approve_and_transfer_from() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 100);
+
+        contract.transfer_from(accounts(2), accounts(1), 60.into(), None);
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 40);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 60);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 60);
+
+        contract.transfer_from(accounts(2), accounts(1), 40.into(), None);
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is not enough")]
+    fn test_transfer_from_over_allowance() {
+        let (_, mut contract) = setup();
+        contract.transfer_from(accounts(2), accounts(1), 101.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of at least 1 yoctoNEAR")]
+    fn test_approve_requires_one_yocto() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.approve(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_DEPOSIT: The attached deposit should be at least")]
+    fn test_approve_without_storage_deposit() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.approve(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    fn test_approve_existing_allowance_without_storage_deposit() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.approve(accounts(3), 50.into(), None);
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_REGISTERED: The account danny is not registered")]
+    fn test_approve_not_registered() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(STORAGE_DEPOSIT).build());
+        contract.approve(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "An account can't approve more than 16 spenders")]
+    fn test_approve_too_many_spenders() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .build());
+        for i in 1..=MAX_ALLOWANCES_PER_OWNER {
+            contract.approve(format!("spender{}.near", i).parse().unwrap(), 1.into(), None);
+        }
+    }
+
+    #[test]
+    fn test_approve_zero_frees_a_spender() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .build());
+        for i in 1..MAX_ALLOWANCES_PER_OWNER {
+            contract.approve(format!("spender{}.near", i).parse().unwrap(), 1.into(), None);
+        }
+        contract.approve(accounts(3), 0.into(), None);
+        contract.approve(accounts(4), 1.into(), None);
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 0);
+        assert_eq!(contract.allowance(accounts(2), accounts(4)).0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MEMO_REQUIRED: A memo is required for transfers")]
+    fn test_transfer_from_memo_required() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.set_require_memo(true);
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.transfer_from(accounts(2), accounts(1), 60.into(), None);
+    }
+
+    #[test]
+    fn test_sponsored_transfer() {
+        let (_, mut contract) = setup();
+        contract.sponsored_transfer(accounts(2), accounts(1), 60.into(), None);
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 40);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 60);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 60);
+        let logs = get_logs();
+        let event: serde_json::Value =
+            serde_json::from_str(logs.last().unwrap().strip_prefix("EVENT_JSON:").unwrap())
+                .unwrap();
+        assert_eq!(event["event"], "sponsored_transfer");
+        assert_eq!(
+            event["data"][0],
+            json!({
+                "owner_id": accounts(2),
+                "receiver_id": accounts(1),
+                "relayer_id": accounts(3),
+                "amount": "60",
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is not enough")]
+    fn test_sponsored_transfer_allowance_exhausted() {
+        let (_, mut contract) = setup();
+        contract.sponsored_transfer(accounts(2), accounts(1), 100.into(), None);
+        contract.sponsored_transfer(accounts(2), accounts(1), 1.into(), None);
+    }
+
+    #[test]
+    fn test_transfer_from_before_expiry() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100)
+            .build());
+        contract.approve(accounts(3), 100.into(), Some(U64(1_000)));
+        assert_eq!(
+            contract.allowance_with_expiry(accounts(2), accounts(3)),
+            (U128(100), Some(U64(1_000)))
+        );
+
+        testing_env!(context
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(1_000)
+            .build());
+        contract.transfer_from(accounts(2), accounts(1), 60.into(), None);
+        assert_eq!(
+            contract.allowance_with_expiry(accounts(2), accounts(3)),
+            (U128(40), Some(U64(1_000)))
+        );
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 60);
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is not enough")]
+    fn test_transfer_from_after_expiry() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100)
+            .build());
+        contract.approve(accounts(3), 100.into(), Some(U64(1_000)));
+
+        testing_env!(context
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(1_001)
+            .build());
+        assert_eq!(contract.allowance_with_expiry(accounts(2), accounts(3)), (U128(0), None));
+        contract.transfer_from(accounts(2), accounts(1), 1.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The expiry should be in the future")]
+    fn test_approve_expired() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(100)
+            .build());
+        contract.approve(accounts(3), 100.into(), Some(U64(99)));
+    }
+}
@@ -0,0 +1,411 @@
+use crate::*;
+use near_contract_standards::fungible_token::events::{FtBurn, FtTransfer};
+use near_sdk::{assert_one_yocto, serde_json, PromiseResult};
+
+/// The maximum number of transfers in a single `ft_transfer_batch` call, to keep it within the gas
+/// limit.
+const MAX_BATCH_TRANSFERS: usize = 100;
+/// The maximum number of receivers in a single `ft_transfer_call_batch` call. Every receiver gets
+/// its own `ft_on_transfer` call with a share of the prepaid gas, so the maximum prepaid gas
+/// doesn't fit more.
+const MAX_BATCH_TRANSFER_CALLS: usize = 5;
+/// The minimum gas of every `ft_on_transfer` call of `ft_transfer_call_batch`.
+const MIN_GAS_FOR_BATCH_ON_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_batch_resolver)]
+trait ResolveTransferBatch {
+    fn ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: AccountId,
+        receivers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128>;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens from the caller to every receiver in `transfers`. Requires exactly one
+    /// yoctoNEAR attached, like `ft_transfer`. If any of the transfers fails, the whole batch
+    /// fails. A single `ft_transfer` event covering all receivers and the charged fees is
+    /// emitted.
+    #[payable]
+    pub fn ft_transfer_batch(&mut self, transfers: Vec<(AccountId, U128)>, memo: Option<String>) {
+        assert_one_yocto();
+        assert!(!transfers.is_empty(), "The batch should not be empty");
+        assert!(
+            transfers.len() <= MAX_BATCH_TRANSFERS,
+            "The batch can't contain more than {} transfers",
+            MAX_BATCH_TRANSFERS
+        );
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_batch(&sender_id, &transfers, memo.as_deref());
+    }
+
+    /// Transfers tokens from the caller to every receiver in `receivers` with the same `memo` and
+    /// calls `ft_on_transfer` on each of them with the same `msg`. The unused tokens of every
+    /// receiver are refunded in a single `ft_resolve_transfer_batch` callback, which returns the
+    /// used amount of each receiver. The gas left after this call and the callback is split
+    /// evenly between the `ft_on_transfer` calls, up to the gas `ft_transfer_call` attaches to
+    /// it. Requires exactly one yoctoNEAR attached and at least 10 Tgas for every receiver.
+    #[payable]
+    pub fn ft_transfer_call_batch(
+        &mut self,
+        receivers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<Vec<U128>> {
+        assert_one_yocto();
+        assert!(!receivers.is_empty(), "The batch should not be empty");
+        assert!(
+            receivers.len() <= MAX_BATCH_TRANSFER_CALLS,
+            "The batch can't contain more than {} transfer calls",
+            MAX_BATCH_TRANSFER_CALLS
+        );
+        let legs = receivers.len() as u64;
+        let resolve_gas = Gas(self.resolve_gas.0 * legs);
+        let reserved_gas = core_impl::GAS_FOR_FT_TRANSFER_CALL.0 + resolve_gas.0;
+        let required_gas = reserved_gas + MIN_GAS_FOR_BATCH_ON_TRANSFER.0 * legs;
+        assert!(
+            env::prepaid_gas().0 >= required_gas,
+            "More gas is required, at least {} is needed",
+            required_gas
+        );
+        let available_gas = env::prepaid_gas().0.saturating_sub(env::used_gas().0 + reserved_gas);
+        let on_transfer_gas = Gas(std::cmp::min(self.on_transfer_gas.0, available_gas / legs));
+        let sender_id = env::predecessor_account_id();
+        let net_amounts = self.internal_transfer_batch(&sender_id, &receivers, memo.as_deref());
+        let resolved_receivers: Vec<(AccountId, U128)> = receivers
+            .into_iter()
+            .zip(net_amounts.into_iter())
+            .map(|((receiver_id, _), net_amount)| (receiver_id, net_amount))
+            .collect();
+        let on_transfer_calls = resolved_receivers
+            .iter()
+            .map(|(receiver_id, net_amount)| {
+                core_impl::ext_ft_receiver::ft_on_transfer(
+                    sender_id.clone(),
+                    *net_amount,
+                    msg.clone(),
+                    receiver_id.clone(),
+                    core_impl::NO_DEPOSIT,
+                    on_transfer_gas,
+                )
+            })
+            .reduce(|calls, call| calls.and(call))
+            .unwrap();
+        on_transfer_calls
+            .then(ext_batch_resolver::ft_resolve_transfer_batch(
+                sender_id,
+                resolved_receivers,
+                env::current_account_id(),
+                core_impl::NO_DEPOSIT,
+                resolve_gas,
+            ))
+            .into()
+    }
+
+    /// Resolves every leg of `ft_transfer_call_batch` the same way `ft_resolve_transfer` does,
+    /// in the order of `receivers`, including the refund policy on receiver panics. Returns the
+    /// used amount of each receiver.
+    #[private]
+    pub fn ft_resolve_transfer_batch(
+        &mut self,
+        sender_id: AccountId,
+        receivers: Vec<(AccountId, U128)>,
+    ) -> Vec<U128> {
+        receivers
+            .into_iter()
+            .enumerate()
+            .map(|(index, (receiver_id, amount))| {
+                if self.internal_keep_on_receiver_panic(index as u64, &receiver_id) {
+                    return amount;
+                }
+                let unused_amount = match env::promise_result(index as u64) {
+                    PromiseResult::NotReady => env::abort(),
+                    PromiseResult::Successful(value) => serde_json::from_slice::<U128>(&value)
+                        .map_or(amount.0, |unused_amount| std::cmp::min(amount.0, unused_amount.0)),
+                    PromiseResult::Failed => amount.0,
+                };
+                self.internal_resolve_leg(&sender_id, &receiver_id, amount.0, unused_amount).into()
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Moves the tokens of every transfer from `sender_id` charging the transfer fee, and emits
+    /// a single `ft_transfer` event covering all receivers and the fees. Returns the amount
+    /// credited to each receiver.
+    fn internal_transfer_batch(
+        &mut self,
+        sender_id: &AccountId,
+        transfers: &[(AccountId, U128)],
+        memo: Option<&str>,
+    ) -> Vec<U128> {
+        self.assert_no_transfer_hook();
+        self.assert_memo_present(memo);
+        self.assert_valid_memo(memo);
+        self.internal_apply_transfer_cooldown(sender_id);
+        let mut net_amounts = Vec::with_capacity(transfers.len());
+        let mut total_fee: Balance = 0;
+        for (receiver_id, amount) in transfers.iter() {
+            let (net_amount, fee) =
+                self.internal_transfer_with_fee(sender_id, receiver_id, amount.0);
+            net_amounts.push(U128(net_amount));
+            total_fee += fee;
+        }
+        let total_fee = U128(total_fee);
+        let mut events: Vec<FtTransfer> = transfers
+            .iter()
+            .zip(net_amounts.iter())
+            .map(|((receiver_id, _), net_amount)| FtTransfer {
+                old_owner_id: sender_id,
+                new_owner_id: receiver_id,
+                amount: net_amount,
+                memo,
+            })
+            .collect();
+        if total_fee.0 > 0 {
+            events.push(FtTransfer {
+                old_owner_id: sender_id,
+                new_owner_id: &self.fee_collector,
+                amount: &total_fee,
+                memo: Some("Transfer fee"),
+            });
+        }
+        FtTransfer::emit_many(&events);
+        net_amounts
+    }
+
+    /// Refunds up to `unused_amount` tokens from the receiver back to the sender, or burns them
+    /// if the sender is gone. Returns the used amount.
+    fn internal_resolve_leg(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        unused_amount: Balance,
+    ) -> Balance {
+        self.internal_settle_reflection(sender_id);
+        self.internal_settle_reflection(receiver_id);
+        let receiver_balance = self.token.accounts.get(receiver_id).unwrap_or(0);
+        if unused_amount == 0 || receiver_balance == 0 {
+            return amount;
+        }
+        let refund_amount = std::cmp::min(receiver_balance, unused_amount);
+        self.token.accounts.insert(receiver_id, &(receiver_balance - refund_amount));
+        self.internal_update_holders(receiver_balance, receiver_balance - refund_amount);
+        if let Some(sender_balance) = self.token.accounts.get(sender_id) {
+            self.token.accounts.insert(sender_id, &(sender_balance + refund_amount));
+            self.internal_update_holders(sender_balance, sender_balance + refund_amount);
+            FtTransfer {
+                old_owner_id: receiver_id,
+                new_owner_id: sender_id,
+                amount: &U128(refund_amount),
+                memo: Some("refund"),
+            }
+            .emit();
+            amount - refund_amount
+        } else {
+            self.token.total_supply -= refund_amount;
+            FtBurn { owner_id: receiver_id, amount: &U128(refund_amount), memo: Some("refund") }
+                .emit();
+            self.on_tokens_burned(sender_id.clone(), refund_amount);
+            amount
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_transfer_batch() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer_batch(
+            vec![(accounts(1), 10.into()), (accounts(3), 20.into())],
+            Some("Payout".to_string()),
+        );
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 20);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 30);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MEMO_REQUIRED: A memo is required for transfers")]
+    fn test_transfer_batch_memo_required() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_require_memo(true);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer_batch(vec![(accounts(1), 10.into())], None);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not registered")]
+    fn test_transfer_batch_unregistered_receiver() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer_batch(vec![(accounts(1), 10.into()), (accounts(3), 20.into())], None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn test_transfer_batch_requires_one_yocto() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer_batch(vec![(accounts(1), 10.into())], None);
+    }
+
+    #[test]
+    fn test_transfer_call_batch_sums_used_amounts() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .prepaid_gas(Gas(300_000_000_000_000))
+            .build());
+        let receivers = vec![(accounts(1), U128(100)), (accounts(3), U128(50))];
+        contract.ft_transfer_call_batch(receivers.clone(), None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 150);
+
+        // The first receiver returns 10 unused tokens, the second one uses everything.
+        testing_env!(
+            context.attached_deposit(0).predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![
+                PromiseResult::Successful(serde_json::to_vec(&U128(10)).unwrap()),
+                PromiseResult::Successful(serde_json::to_vec(&U128(0)).unwrap()),
+            ]
+        );
+        let used_amounts = contract.ft_resolve_transfer_batch(accounts(2), receivers);
+
+        assert_eq!(used_amounts, vec![U128(90), U128(50)]);
+        assert_eq!(used_amounts.iter().map(|amount| amount.0).sum::<Balance>(), 140);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 90);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 50);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 140);
+    }
+
+    /// Registers `count` receivers, the owner transferring `ft_transfer_call_batch` to all of
+    /// them with the maximum prepaid gas.
+    fn setup_full_batch(count: usize) -> (VMContextBuilder, Contract, Vec<(AccountId, U128)>) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let receivers: Vec<(AccountId, U128)> = (0..count)
+            .map(|index| (format!("receiver{}.near", index).parse().unwrap(), U128(10)))
+            .collect();
+        for (receiver_id, _) in receivers.iter() {
+            register_account(&mut context, &mut contract, receiver_id.clone());
+        }
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .prepaid_gas(Gas(300_000_000_000_000))
+            .build());
+        (context, contract, receivers)
+    }
+
+    #[test]
+    fn test_transfer_call_full_batch_at_max_gas() {
+        let (_, mut contract, receivers) = setup_full_batch(MAX_BATCH_TRANSFER_CALLS);
+        contract.ft_transfer_call_batch(receivers, None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 50);
+        // An `ft_on_transfer` call for every receiver and the resolve callback.
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), MAX_BATCH_TRANSFER_CALLS + 1);
+        assert!(format!("{:?}", receipts[0].actions).contains("ft_on_transfer"));
+    }
+
+    #[test]
+    fn test_transfer_call_batch_with_raised_on_transfer_gas() {
+        let (mut context, mut contract, receivers) = setup_full_batch(2);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_transfer_call_gas(
+            Gas(250_000_000_000_000),
+            core_impl::DEFAULT_GAS_FOR_RESOLVE_TRANSFER,
+        );
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer_call_batch(receivers, None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "More gas is required, at least 100000000000000 is needed")]
+    fn test_transfer_call_batch_not_enough_gas() {
+        let (mut context, mut contract, receivers) = setup_full_batch(MAX_BATCH_TRANSFER_CALLS);
+        testing_env!(context.prepaid_gas(Gas(99_999_999_999_999)).build());
+        contract.ft_transfer_call_batch(receivers, None, "".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The batch can't contain more than 5 transfer calls")]
+    fn test_transfer_call_batch_too_long() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.attached_deposit(1).build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.ft_transfer_call_batch(vec![(accounts(1), U128(1)); 6], None, "".to_string());
+    }
+}
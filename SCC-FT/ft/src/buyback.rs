@@ -0,0 +1,180 @@
+//! Buybacks of the treasury: the owner buys tokens from a seller with the attached NEAR and
+//! burns them. The seller first approves the token contract account to spend the sold tokens
+//! with `approve`. The tokens are burned right away and the NEAR is sent to the seller; if the
+//! NEAR transfer fails, e.g. because the seller account was deleted, the `on_buyback_paid`
+//! callback credits the tokens back to the seller and refunds the NEAR to the buyer.
+use crate::*;
+use near_contract_standards::fungible_token::events::FtMint;
+use near_sdk::is_promise_success;
+
+/// The gas attached to the `on_buyback_paid` callback.
+const GAS_FOR_ON_BUYBACK_PAID: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_buyback_resolver)]
+trait BuybackResolver {
+    fn on_buyback_paid(
+        &mut self,
+        seller: AccountId,
+        token_amount: U128,
+        price: U128,
+        buyer: AccountId,
+    ) -> bool;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Buys `token_amount` tokens from `seller` for the attached NEAR and burns them, spending
+    /// the allowance the seller gave to the token contract account. Can only be called by the
+    /// owner.
+    #[payable]
+    pub fn buyback_burn(&mut self, seller: AccountId, token_amount: U128) -> Promise {
+        self.assert_owner();
+        let price = env::attached_deposit();
+        scc_assert!(
+            price > 0,
+            SccError::InsufficientDeposit,
+            "The NEAR price of the tokens has to be attached"
+        );
+        scc_assert!(
+            token_amount.0 > 0,
+            SccError::ZeroAmount,
+            "The amount should be a positive number"
+        );
+        self.internal_spend_allowance(&seller, &env::current_account_id(), token_amount.0);
+        self.internal_burn(&seller, token_amount, Some("Buyback".to_string()));
+        Promise::new(seller.clone()).transfer(price).then(ext_buyback_resolver::on_buyback_paid(
+            seller,
+            token_amount,
+            U128(price),
+            env::predecessor_account_id(),
+            env::current_account_id(),
+            core_impl::NO_DEPOSIT,
+            GAS_FOR_ON_BUYBACK_PAID,
+        ))
+    }
+
+    /// Completes a buyback, or reverts it if the NEAR couldn't be sent to the seller: the burned
+    /// tokens and the spent allowance are restored, unless the seller unregistered in the
+    /// meantime, and the NEAR that bounced back is refunded to the buyer. Returns `true` if the
+    /// buyback succeeded.
+    #[private]
+    pub fn on_buyback_paid(
+        &mut self,
+        seller: AccountId,
+        token_amount: U128,
+        price: U128,
+        buyer: AccountId,
+    ) -> bool {
+        if is_promise_success() {
+            log!(
+                "Bought back {} tokens from @{} for {} yoctoNEAR",
+                token_amount.0,
+                seller,
+                price.0
+            );
+            return true;
+        }
+        if self.token.accounts.contains_key(&seller) {
+            self.internal_settle_reflection(&seller);
+            self.internal_deposit(&seller, token_amount.0);
+            self.total_burned -= token_amount.0;
+            FtMint { owner_id: &seller, amount: &token_amount, memo: Some("Buyback refund") }
+                .emit();
+            self.internal_update_supply_warning();
+            let contract_id = env::current_account_id();
+            let (allowance, expiry_ns) = self.internal_allowance(&seller, &contract_id);
+            self.internal_set_allowance(
+                &seller,
+                &contract_id,
+                allowance + token_amount.0,
+                expiry_ns,
+            );
+        }
+        log!("Failed to pay @{} for the buyback, refunding {} yoctoNEAR", seller, price.0);
+        Promise::new(buyer).transfer(price.0);
+        false
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{
+        get_context, init_contract, register_account, STORAGE_DEPOSIT, TOTAL_SUPPLY,
+    };
+
+    const PRICE: Balance = 10_000_000_000_000_000_000_000_000;
+
+    /// Gives 500 tokens to `accounts(1)`, which approves the contract to spend `approved` of
+    /// them, and switches to the owner attaching `PRICE`.
+    fn setup(approved: Balance) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 500.into(), None);
+        testing_env!(context
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.approve(accounts(0), approved.into(), None);
+        testing_env!(context.predecessor_account_id(accounts(2)).attached_deposit(PRICE).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_buyback_burn() {
+        let (mut context, mut contract) = setup(200);
+        contract.buyback_burn(accounts(1), 200.into());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 300);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 200);
+        assert_eq!(contract.ft_total_burned().0, 200);
+        assert_eq!(contract.allowance(accounts(1), accounts(0)).0, 0);
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+        assert!(contract.on_buyback_paid(accounts(1), 200.into(), PRICE.into(), accounts(2)));
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 200);
+    }
+
+    #[test]
+    fn test_buyback_burn_payment_failed() {
+        let (mut context, mut contract) = setup(200);
+        contract.buyback_burn(accounts(1), 200.into());
+
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).attached_deposit(0).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        assert!(!contract.on_buyback_paid(accounts(1), 200.into(), PRICE.into(), accounts(2)));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 500);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+        assert_eq!(contract.ft_total_burned().0, 0);
+        assert_eq!(contract.allowance(accounts(1), accounts(0)).0, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "The allowance is not enough")]
+    fn test_buyback_burn_insufficient_approval() {
+        let (_, mut contract) = setup(100);
+        contract.buyback_burn(accounts(1), 200.into());
+    }
+}
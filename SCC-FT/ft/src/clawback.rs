@@ -0,0 +1,106 @@
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::serde_json::json;
+
+#[near_bindgen]
+impl Contract {
+    /// Moves `amount` tokens from the given account to the owner, e.g. under a court order.
+    /// Works on frozen and blacklisted accounts and while transfers are paused. Emits a
+    /// `clawback` event with the reason. Can only be called by the owner.
+    pub fn clawback(&mut self, account_id: AccountId, amount: U128, reason: String) {
+        self.assert_owner();
+        assert!(!reason.is_empty(), "The reason can't be empty");
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        let owner_id = self.owner_id.clone();
+        assert_ne!(account_id, owner_id, "Can't claw back tokens from the owner");
+        self.internal_settle_reflection(&account_id);
+        self.internal_settle_reflection(&owner_id);
+        self.internal_withdraw(&account_id, amount.0);
+        self.internal_deposit(&owner_id, amount.0);
+        FtTransfer {
+            old_owner_id: &account_id,
+            new_owner_id: &owner_id,
+            amount: &amount,
+            memo: Some("Clawback"),
+        }
+        .emit();
+        self.emit_event(
+            "clawback",
+            &[json!({
+                "account_id": account_id,
+                "receiver_id": owner_id,
+                "amount": amount,
+                "reason": reason,
+            })],
+        );
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{serde_json, testing_env};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_clawback() {
+        let (_, mut contract) = setup();
+        contract.freeze_account(accounts(1));
+        contract.clawback(accounts(1), 60.into(), "Court order 123".to_string());
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 40);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 40);
+        let logs = get_logs();
+        let event: serde_json::Value =
+            serde_json::from_str(logs.last().unwrap().strip_prefix("EVENT_JSON:").unwrap())
+                .unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "standard": "scc",
+                "version": "1.0.0",
+                "event": "clawback",
+                "data": [{
+                    "account_id": accounts(1),
+                    "receiver_id": accounts(2),
+                    "amount": "60",
+                    "reason": "Court order 123"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn test_clawback_above_balance() {
+        let (_, mut contract) = setup();
+        contract.clawback(accounts(1), 101.into(), "Court order 123".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_clawback_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.clawback(accounts(1), 10.into(), "Court order 123".to_string());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::*;
+use near_sdk::serde::Serialize;
+
+/// All the settings of the contract, as returned by their individual getters.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    pub owner_id: AccountId,
+    pub pending_owner: Option<AccountId>,
+    pub ownership_renounced: bool,
+    pub multisig_owners: Vec<AccountId>,
+    pub multisig_threshold: u8,
+    pub paused: bool,
+    pub wrapped: bool,
+    pub max_supply: Option<U128>,
+    pub supply_warning_bps: Option<u16>,
+    pub mint_limit_per_day: Option<U128>,
+    pub transfer_fee_bps: u16,
+    pub fee_collector: AccountId,
+    pub burn_rate_bps: u16,
+    pub rounding_mode: RoundingMode,
+    pub reflection_fee_bps: u16,
+    pub min_transfer_amount: U128,
+    pub large_transfer_threshold: U128,
+    pub transfer_cooldown_seconds: u64,
+    pub transfer_whitelist_enabled: bool,
+    pub require_memo: bool,
+    pub max_memo_bytes: u32,
+    pub allow_self_transfer: bool,
+    pub allow_zero_transfers: bool,
+    pub refund_on_receiver_panic: bool,
+    pub transfer_hook: Option<AccountId>,
+    pub on_new_holder_contract: Option<AccountId>,
+    pub timelock_delay: U64,
+    pub transfer_call_gas: TransferCallGas,
+    pub max_recent_transfers: u64,
+    pub event_version: String,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns all the settings of the contract in a single call.
+    pub fn get_config(&self) -> Config {
+        let (multisig_owners, multisig_threshold) = self.get_multisig();
+        Config {
+            owner_id: self.owner_id.clone(),
+            pending_owner: self.pending_owner.clone(),
+            ownership_renounced: self.ownership_renounced,
+            multisig_owners,
+            multisig_threshold,
+            paused: self.paused,
+            wrapped: self.wrapped,
+            max_supply: self.max_supply.map(U128),
+            supply_warning_bps: self.supply_warning_bps,
+            mint_limit_per_day: self.mint_limit_per_day.map(U128),
+            transfer_fee_bps: self.transfer_fee_bps,
+            fee_collector: self.fee_collector.clone(),
+            burn_rate_bps: self.burn_rate_bps,
+            rounding_mode: self.rounding_mode,
+            reflection_fee_bps: self.reflection_fee_bps,
+            min_transfer_amount: self.min_transfer_amount.into(),
+            large_transfer_threshold: self.large_transfer_threshold.into(),
+            transfer_cooldown_seconds: self.transfer_cooldown_seconds,
+            transfer_whitelist_enabled: self.transfer_whitelist_enabled,
+            require_memo: self.require_memo,
+            max_memo_bytes: self.max_memo_bytes,
+            allow_self_transfer: self.allow_self_transfer,
+            allow_zero_transfers: self.allow_zero_transfers,
+            refund_on_receiver_panic: self.refund_on_receiver_panic,
+            transfer_hook: self.transfer_hook.clone(),
+            on_new_holder_contract: self.on_new_holder_contract.clone(),
+            timelock_delay: self.timelock_delay.into(),
+            transfer_call_gas: self.get_transfer_call_gas(),
+            max_recent_transfers: self.max_recent_transfers,
+            event_version: self.event_version.clone(),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_get_config() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.pause();
+        contract.set_burn_rate(100);
+        contract.set_min_transfer_amount(10.into());
+        contract.set_transfer_cooldown(60);
+        contract.set_require_memo(true);
+
+        let config = contract.get_config();
+        assert_eq!(config.owner_id, contract.get_owner());
+        assert_eq!(config.pending_owner, contract.get_pending_owner());
+        assert_eq!(
+            (config.multisig_owners.clone(), config.multisig_threshold),
+            contract.get_multisig()
+        );
+        assert_eq!(config.paused, contract.is_paused());
+        assert_eq!(config.wrapped, contract.is_wrapped());
+        assert_eq!(config.max_supply, contract.get_max_supply());
+        assert_eq!(config.mint_limit_per_day, contract.get_mint_limit());
+        assert_eq!(
+            (config.transfer_fee_bps, config.fee_collector.clone()),
+            contract.get_transfer_fee()
+        );
+        assert_eq!(config.burn_rate_bps, contract.get_burn_rate());
+        assert_eq!(config.reflection_fee_bps, contract.get_reflection_fee());
+        assert_eq!(config.min_transfer_amount, contract.get_min_transfer_amount());
+        assert_eq!(config.large_transfer_threshold, contract.get_large_transfer_threshold());
+        assert_eq!(config.transfer_cooldown_seconds, contract.get_transfer_cooldown());
+        assert_eq!(config.transfer_whitelist_enabled, contract.is_transfer_whitelist_enabled());
+        assert_eq!(config.require_memo, contract.is_memo_required());
+        assert_eq!(config.allow_self_transfer, contract.is_self_transfer_allowed());
+        assert_eq!(config.timelock_delay, contract.get_timelock_delay());
+        assert_eq!(config.transfer_call_gas, contract.get_transfer_call_gas());
+        assert_eq!(config.event_version, contract.get_event_version());
+
+        assert!(config.paused);
+        assert_eq!(config.burn_rate_bps, 100);
+        assert_eq!(config.min_transfer_amount.0, 10);
+        assert_eq!(config.transfer_cooldown_seconds, 60);
+        assert!(config.require_memo);
+    }
+}
@@ -0,0 +1,522 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json::json;
+use near_sdk::PromiseResult;
+
+/// The gas reserved for the execution of `ft_transfer_call` itself.
+pub(crate) const GAS_FOR_FT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000);
+/// The minimum gas for `ft_resolve_transfer`, below which refunds could fail.
+const MIN_GAS_FOR_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+/// The maximum gas that can be attached to a function call.
+const MAX_PREPAID_GAS: Gas = Gas(300_000_000_000_000);
+pub(crate) const DEFAULT_GAS_FOR_RESOLVE_TRANSFER: Gas = MIN_GAS_FOR_RESOLVE_TRANSFER;
+pub(crate) const DEFAULT_GAS_FOR_ON_TRANSFER: Gas = Gas(50_000_000_000_000);
+pub(crate) const NO_DEPOSIT: Balance = 0;
+
+/// The gas attached to the calls made by `ft_transfer_call`.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferCallGas {
+    pub on_transfer_gas: Gas,
+    pub resolve_gas: Gas,
+}
+
+#[ext_contract(ext_ft_receiver)]
+pub trait FungibleTokenReceiver {
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[ext_contract(ext_self)]
+trait ResolveTransfer {
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128;
+}
+
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    /// Transfers tokens to `receiver_id`. A transfer to the sender itself is a no-op when self
+    /// transfers are allowed and panics otherwise.
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        self.assert_nonzero_transfer(amount.0);
+        self.assert_memo_present(memo.as_deref());
+        let sender_id = env::predecessor_account_id();
+        if sender_id == receiver_id {
+            scc_assert!(
+                self.allow_self_transfer,
+                SccError::SelfTransfer,
+                "Sender and receiver should be different"
+            );
+            return;
+        }
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.into(), memo);
+    }
+
+    /// Transfers tokens to `receiver_id` and calls `ft_on_transfer` on it. The transfer fee is
+    /// charged upfront, so the receiver is notified about the amount it was actually credited and
+    /// only that amount can be refunded.
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        assert_one_yocto();
+        let required_gas = GAS_FOR_FT_TRANSFER_CALL
+            + self.on_transfer_gas
+            + self.resolve_gas
+            + self.internal_transfer_hook_gas();
+        assert!(
+            env::prepaid_gas() >= required_gas,
+            "More gas is required, at least {} is needed",
+            required_gas.0
+        );
+        self.assert_nonzero_transfer(amount.0);
+        self.assert_memo_present(memo.as_deref());
+        let sender_id = env::predecessor_account_id();
+        if let Some(transfer_hook) = self.transfer_hook.clone() {
+            return self
+                .internal_check_transfer_hook(
+                    transfer_hook,
+                    sender_id,
+                    receiver_id,
+                    amount,
+                    memo,
+                    msg,
+                )
+                .into();
+        }
+        let net_amount = self.internal_ft_transfer(&sender_id, &receiver_id, amount.into(), memo);
+        self.internal_notify_receiver(sender_id, receiver_id, net_amount, msg).into()
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.token.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: AccountId) -> U128 {
+        self.internal_balance_of(&account_id).into()
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    /// Refunds the unused tokens to the sender, emitting an `ft_refund` event when some tokens
+    /// are actually refunded. The balances are read when the callback runs, so if the receiver
+    /// already moved some of the tokens away, the refund is capped at its current balance and
+    /// can never underflow it. If `ft_on_transfer` panicked and refunds on receiver panics are
+    /// disabled, all the tokens stay with the receiver.
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) -> U128 {
+        if self.internal_keep_on_receiver_panic(0, &receiver_id) {
+            return amount;
+        }
+        self.internal_settle_reflection(&sender_id);
+        self.internal_settle_reflection(&receiver_id);
+        let sender_balance = self.token.accounts.get(&sender_id).unwrap_or(0);
+        let receiver_balance = self.token.accounts.get(&receiver_id).unwrap_or(0);
+        let (used_amount, burned_amount) =
+            self.token.internal_ft_resolve_transfer(&sender_id, receiver_id.clone(), amount);
+        self.internal_update_holders(
+            sender_balance,
+            self.token.accounts.get(&sender_id).unwrap_or(0),
+        );
+        self.internal_update_holders(
+            receiver_balance,
+            self.token.accounts.get(&receiver_id).unwrap_or(0),
+        );
+        if burned_amount > 0 {
+            self.on_tokens_burned(sender_id, burned_amount);
+        } else if used_amount < amount.0 {
+            self.emit_event(
+                "ft_refund",
+                &[json!({
+                    "sender_id": sender_id,
+                    "receiver_id": receiver_id,
+                    "refunded_amount": U128(amount.0 - used_amount),
+                })],
+            );
+        }
+        used_amount.into()
+    }
+}
+
+impl Contract {
+    /// Returns `true` if the receiver call of the promise result at `result_index` panicked and
+    /// the tokens have to stay with the receiver instead of being refunded.
+    pub(crate) fn internal_keep_on_receiver_panic(
+        &self,
+        result_index: u64,
+        receiver_id: &AccountId,
+    ) -> bool {
+        if self.refund_on_receiver_panic
+            || !matches!(env::promise_result(result_index), PromiseResult::Failed)
+        {
+            return false;
+        }
+        log!("The receiver @{} failed, the tokens stay credited to it", receiver_id);
+        true
+    }
+
+    /// Calls `ft_on_transfer` on the receiver of `net_amount` tokens and resolves the refund of
+    /// the unused tokens in `ft_resolve_transfer`.
+    pub(crate) fn internal_notify_receiver(
+        &self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        net_amount: Balance,
+        msg: String,
+    ) -> Promise {
+        ext_ft_receiver::ft_on_transfer(
+            sender_id.clone(),
+            net_amount.into(),
+            msg,
+            receiver_id.clone(),
+            NO_DEPOSIT,
+            self.on_transfer_gas,
+        )
+        .then(ext_self::ft_resolve_transfer(
+            sender_id,
+            receiver_id,
+            net_amount.into(),
+            env::current_account_id(),
+            NO_DEPOSIT,
+            self.resolve_gas,
+        ))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the gas attached to `ft_on_transfer` and `ft_resolve_transfer` by
+    /// `ft_transfer_call`.
+    pub fn get_transfer_call_gas(&self) -> TransferCallGas {
+        TransferCallGas { on_transfer_gas: self.on_transfer_gas, resolve_gas: self.resolve_gas }
+    }
+
+    /// Transfers tokens like `ft_transfer`, unless the block timestamp is already past
+    /// `deadline_ns` (in nanoseconds), so that a late relayed transaction can't execute a stale
+    /// transfer. Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn ft_transfer_with_deadline(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        deadline_ns: U64,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        scc_assert!(
+            env::block_timestamp() <= deadline_ns.0,
+            SccError::DeadlinePassed,
+            "The transfer deadline has passed"
+        );
+        let sender_id = env::predecessor_account_id();
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.into(), memo);
+    }
+
+    /// Returns `true` if the tokens of a transfer call are refunded to the sender when the
+    /// receiver's `ft_on_transfer` panics, as the standard requires.
+    pub fn is_refund_on_receiver_panic(&self) -> bool {
+        self.refund_on_receiver_panic
+    }
+
+    /// Sets whether the tokens of a transfer call are refunded to the sender when the receiver's
+    /// `ft_on_transfer` panics, which is the default, or stay credited to the receiver so the
+    /// receiver can process them on a retry. Without refunds, a receiver contract that panics
+    /// on every call, e.g. a contract that doesn't implement `ft_on_transfer` at all, keeps the
+    /// tokens for good, and senders relying on the standard refund lose them. Can only be
+    /// called by the owner.
+    pub fn set_refund_on_receiver_panic(&mut self, refund_on_receiver_panic: bool) {
+        self.assert_owner();
+        self.refund_on_receiver_panic = refund_on_receiver_panic;
+        log!(
+            "Refunds on receiver panics {}",
+            if refund_on_receiver_panic { "enabled" } else { "disabled" }
+        );
+    }
+
+    /// Sets the gas attached to `ft_on_transfer` and `ft_resolve_transfer` by `ft_transfer_call`.
+    /// Can only be called by the owner.
+    pub fn set_transfer_call_gas(&mut self, on_transfer_gas: Gas, resolve_gas: Gas) {
+        self.assert_owner();
+        assert!(
+            resolve_gas >= MIN_GAS_FOR_RESOLVE_TRANSFER,
+            "The resolve gas should be at least {}",
+            MIN_GAS_FOR_RESOLVE_TRANSFER.0
+        );
+        assert!(
+            on_transfer_gas.0 + resolve_gas.0 + GAS_FOR_FT_TRANSFER_CALL.0 <= MAX_PREPAID_GAS.0,
+            "The gas doesn't leave enough for the execution of ft_transfer_call"
+        );
+        self.on_transfer_gas = on_transfer_gas;
+        self.resolve_gas = resolve_gas;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{serde_json, testing_env, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_set_transfer_call_gas() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        assert_eq!(
+            contract.get_transfer_call_gas(),
+            TransferCallGas {
+                on_transfer_gas: DEFAULT_GAS_FOR_ON_TRANSFER,
+                resolve_gas: DEFAULT_GAS_FOR_RESOLVE_TRANSFER
+            }
+        );
+
+        contract.set_transfer_call_gas(Gas(200_000_000_000_000), Gas(10_000_000_000_000));
+        assert_eq!(
+            contract.get_transfer_call_gas(),
+            TransferCallGas {
+                on_transfer_gas: Gas(200_000_000_000_000),
+                resolve_gas: Gas(10_000_000_000_000)
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The gas doesn't leave enough for the execution of ft_transfer_call")]
+    fn test_set_transfer_call_gas_too_high() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_transfer_call_gas(Gas(270_000_000_000_000), Gas(10_000_000_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "More gas is required, at least 235000000000000 is needed")]
+    fn test_transfer_call_uses_configured_gas() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_transfer_call_gas(Gas(200_000_000_000_000), Gas(10_000_000_000_000));
+
+        testing_env!(context.attached_deposit(1).prepaid_gas(Gas(234_000_000_000_000)).build());
+        contract.ft_transfer_call(accounts(1), 10.into(), None, "".to_string());
+    }
+
+    #[test]
+    fn test_transfer_call_with_enough_gas() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .prepaid_gas(Gas(300_000_000_000_000))
+            .build());
+        contract.ft_transfer_call(accounts(1), 10.into(), None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+    }
+
+    #[test]
+    fn test_transfer_before_deadline() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_000)
+            .build());
+        contract.ft_transfer_with_deadline(accounts(1), 10.into(), 1_000.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer deadline has passed")]
+    fn test_transfer_after_deadline() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(1_001)
+            .build());
+        contract.ft_transfer_with_deadline(accounts(1), 10.into(), 1_000.into(), None);
+    }
+
+    /// Transfers 100 tokens to `accounts(1)` and prepares the context of the resolve callback
+    /// where the receiver returned `unused_amount`.
+    fn setup_resolve(unused_amount: Balance) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+
+        testing_env!(
+            context.attached_deposit(0).predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(serde_json::to_vec(&U128(unused_amount)).unwrap())]
+        );
+        (context, contract)
+    }
+
+    #[test]
+    fn test_resolve_refund_not_counted_in_stats() {
+        let (_, mut contract) = setup_resolve(100);
+        contract.ft_resolve_transfer(accounts(2), accounts(1), 100.into());
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.get_transfer_stats(), (U128(100), 1));
+    }
+
+    #[test]
+    fn test_resolve_refund_capped_at_receiver_balance() {
+        let (mut context, mut contract) = setup_resolve(100);
+        // The receiver spends 60 of the received tokens before the callback runs.
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(2), 60.into(), None);
+        testing_env!(
+            context.attached_deposit(0).predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(serde_json::to_vec(&U128(100)).unwrap())]
+        );
+        let used_amount = contract.ft_resolve_transfer(accounts(2), accounts(1), 100.into());
+
+        assert_eq!(used_amount.0, 60);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    fn test_resolve_partial_refund_event() {
+        let (_, mut contract) = setup_resolve(30);
+        let used_amount = contract.ft_resolve_transfer(accounts(2), accounts(1), 100.into());
+
+        assert_eq!(used_amount.0, 70);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 70);
+        // The token logs the refund as a NEP-141 transfer right before the `ft_refund` event.
+        let logs = get_logs();
+        let event: serde_json::Value =
+            serde_json::from_str(logs.last().unwrap().strip_prefix("EVENT_JSON:").unwrap())
+                .unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "standard": "scc",
+                "version": "1.0.0",
+                "event": "ft_refund",
+                "data": [{
+                    "sender_id": accounts(2),
+                    "receiver_id": accounts(1),
+                    "refunded_amount": "30"
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolve_without_refund() {
+        let (_, mut contract) = setup_resolve(0);
+        let used_amount = contract.ft_resolve_transfer(accounts(2), accounts(1), 100.into());
+
+        assert_eq!(used_amount.0, 100);
+        assert!(get_logs().is_empty());
+    }
+
+    /// Resolves a transfer call of 100 tokens to `accounts(1)` whose `ft_on_transfer` panicked,
+    /// with refunds on receiver panics set to `refund_on_receiver_panic`.
+    fn resolve_receiver_panic(refund_on_receiver_panic: bool) -> (Contract, U128) {
+        let (mut context, mut contract) = setup_resolve(0);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.set_refund_on_receiver_panic(refund_on_receiver_panic);
+        assert_eq!(contract.is_refund_on_receiver_panic(), refund_on_receiver_panic);
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        let used_amount = contract.ft_resolve_transfer(accounts(2), accounts(1), 100.into());
+        (contract, used_amount)
+    }
+
+    #[test]
+    fn test_resolve_receiver_panic_refunded() {
+        let (contract, used_amount) = resolve_receiver_panic(true);
+        assert_eq!(used_amount.0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    fn test_resolve_receiver_panic_kept() {
+        let (contract, used_amount) = resolve_receiver_panic(false);
+        assert_eq!(used_amount.0, 100);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 100);
+        assert_eq!(
+            get_logs(),
+            vec!["The receiver @bob failed, the tokens stay credited to it".to_string()]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Sync of the blacklist with a shared denylist registry, a contract listing denied accounts
+//! with a paginated `get_denied(from_index, limit)` view. Every `sync_denylist` call fetches the
+//! next page of the registry and merges it into the blacklist in the `on_denylist_fetched`
+//! callback, so a long denylist is synced by calling it repeatedly. The registry is assumed to
+//! only append accounts, so once the end is reached, later syncs only fetch the new accounts.
+use crate::*;
+use near_sdk::{serde_json, PromiseResult};
+
+/// The number of accounts fetched from the registry by a single `sync_denylist` call.
+const DENYLIST_PAGE_SIZE: u64 = 100;
+/// The gas attached to the `get_denied` call of the registry.
+const GAS_FOR_GET_DENIED: Gas = Gas(10_000_000_000_000);
+/// The gas attached to the `on_denylist_fetched` callback, enough to merge a full page.
+const GAS_FOR_ON_DENYLIST_FETCHED: Gas = Gas(50_000_000_000_000);
+
+#[ext_contract(ext_denylist_registry)]
+trait DenylistRegistry {
+    fn get_denied(&self, from_index: u64, limit: u64) -> Vec<AccountId>;
+}
+
+#[ext_contract(ext_denylist_resolver)]
+trait DenylistResolver {
+    fn on_denylist_fetched(&mut self, registry: AccountId, from_index: u64) -> u64;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the registry the blacklist is synced with and the index of its next account to
+    /// fetch, if the blacklist was ever synced.
+    pub fn get_denylist_sync(&self) -> Option<(AccountId, u64)> {
+        self.denylist_sync.clone()
+    }
+
+    /// Fetches the next page of denied accounts from `registry` and blacklists them. Syncing
+    /// with another registry than the previous one starts from its first account. Can only be
+    /// called by the owner.
+    pub fn sync_denylist(&mut self, registry: AccountId) -> Promise {
+        self.assert_owner();
+        let from_index = match &self.denylist_sync {
+            Some((synced_registry, next_index)) if synced_registry == &registry => *next_index,
+            _ => 0,
+        };
+        ext_denylist_registry::get_denied(
+            from_index,
+            DENYLIST_PAGE_SIZE,
+            registry.clone(),
+            core_impl::NO_DEPOSIT,
+            GAS_FOR_GET_DENIED,
+        )
+        .then(ext_denylist_resolver::on_denylist_fetched(
+            registry,
+            from_index,
+            env::current_account_id(),
+            core_impl::NO_DEPOSIT,
+            GAS_FOR_ON_DENYLIST_FETCHED,
+        ))
+    }
+
+    /// Blacklists the accounts of the page fetched from `registry` at `from_index` that are not
+    /// blacklisted yet and advances the sync past the page. Returns the number of newly
+    /// blacklisted accounts.
+    #[private]
+    pub fn on_denylist_fetched(&mut self, registry: AccountId, from_index: u64) -> u64 {
+        let denied: Vec<AccountId> = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice(&value)
+                .unwrap_or_else(|_| env::panic_str("The registry returned an invalid denylist")),
+            _ => env::panic_str("The denylist couldn't be fetched from the registry"),
+        };
+        let reason = format!("Denied by @{}", registry);
+        let mut added = 0;
+        for account_id in denied.iter() {
+            if self.blacklist.get(account_id).is_none() {
+                self.blacklist.insert(account_id, &reason);
+                added += 1;
+            }
+        }
+        let next_index = from_index + denied.len() as u64;
+        // Concurrent syncs of the same page must not move the sync backwards.
+        let next_index = match &self.denylist_sync {
+            Some((synced_registry, synced_index)) if synced_registry == &registry => {
+                std::cmp::max(*synced_index, next_index)
+            }
+            _ => next_index,
+        };
+        self.denylist_sync = Some((registry.clone(), next_index));
+        log!(
+            "Blacklisted {} accounts denied by @{}, the sync continues at index {}",
+            added,
+            registry,
+            next_index
+        );
+        added
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        (context, contract)
+    }
+
+    /// Runs the callback of a sync of the page at `from_index` where the mock registry returned
+    /// `denied`.
+    fn fetch_page(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        from_index: u64,
+        denied: Vec<AccountId>,
+    ) -> u64 {
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(serde_json::to_vec(&denied).unwrap())]
+        );
+        contract.on_denylist_fetched(accounts(4), from_index)
+    }
+
+    #[test]
+    fn test_sync_denylist() {
+        let (mut context, mut contract) = setup();
+        contract.blacklist_account(accounts(3), "Sanctioned".to_string());
+        contract.sync_denylist(accounts(4));
+
+        assert_eq!(fetch_page(&mut context, &mut contract, 0, vec![accounts(1), accounts(3)]), 1);
+        assert_eq!(
+            contract.get_blacklist_reason(accounts(1)),
+            Some("Denied by @eugene".to_string())
+        );
+        assert_eq!(contract.get_blacklist_reason(accounts(3)), Some("Sanctioned".to_string()));
+        assert_eq!(contract.get_denylist_sync(), Some((accounts(4), 2)));
+
+        // The same page fetched again doesn't add anything or move the sync backwards.
+        assert_eq!(fetch_page(&mut context, &mut contract, 0, vec![accounts(1)]), 0);
+        assert_eq!(contract.get_denylist_sync(), Some((accounts(4), 2)));
+
+        assert_eq!(fetch_page(&mut context, &mut contract, 2, vec![accounts(5)]), 1);
+        assert!(contract.get_blacklist_reason(accounts(5)).is_some());
+        assert_eq!(contract.get_denylist_sync(), Some((accounts(4), 3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The denylist couldn't be fetched from the registry")]
+    fn test_sync_denylist_failed() {
+        let (mut context, mut contract) = setup();
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        contract.on_denylist_fetched(accounts(4), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_sync_denylist_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.sync_denylist(accounts(4));
+    }
+}
@@ -0,0 +1,157 @@
+//! Emergency exits during a pause. An account registers an emergency recipient, e.g. a cold
+//! wallet, while transfers are running. If the owner pauses the transfers for an incident, the
+//! account can still move its whole balance to that recipient with `emergency_withdraw`. The
+//! recipient can't be changed while paused, so a compromised key can't redirect the exit.
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::assert_one_yocto;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the account the given account can withdraw to while transfers are paused, if any.
+    pub fn get_emergency_recipient(&self, account_id: AccountId) -> Option<AccountId> {
+        self.emergency_recipients.get(&account_id)
+    }
+
+    /// Sets the registered account the caller can withdraw its balance to while transfers are
+    /// paused, or removes it with `None`. Can't be called while transfers are paused. Requires
+    /// exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn set_emergency_recipient(&mut self, recipient: Option<AccountId>) {
+        assert_one_yocto();
+        scc_assert!(
+            !self.paused,
+            SccError::Paused,
+            "The emergency recipient can't be changed while transfers are paused"
+        );
+        let account_id = env::predecessor_account_id();
+        scc_assert!(
+            self.token.accounts.contains_key(&account_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            account_id
+        );
+        match recipient {
+            Some(recipient) => {
+                assert_ne!(account_id, recipient, "Can't set yourself as the emergency recipient");
+                scc_assert!(
+                    self.token.accounts.contains_key(&recipient),
+                    SccError::NotRegistered,
+                    "The account {} is not registered",
+                    recipient
+                );
+                log!("Account @{} set @{} as its emergency recipient", account_id, recipient);
+                self.emergency_recipients.insert(&account_id, &recipient);
+            }
+            None => {
+                log!("Account @{} removed its emergency recipient", account_id);
+                self.emergency_recipients.remove(&account_id);
+            }
+        }
+    }
+
+    /// Moves the whole balance of the caller to its emergency recipient `to` while transfers are
+    /// paused. No fees are charged, but frozen and blacklisted accounts still can't move tokens.
+    /// Returns the withdrawn amount. Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn emergency_withdraw(&mut self, to: AccountId) -> U128 {
+        assert_one_yocto();
+        assert!(self.paused, "Emergency withdrawals are only possible while transfers are paused");
+        let account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.emergency_recipients.get(&account_id).as_ref(),
+            Some(&to),
+            "The account {} is not the emergency recipient of @{}",
+            to,
+            account_id
+        );
+        self.assert_not_frozen(&account_id);
+        self.assert_not_frozen(&to);
+        self.internal_settle_reflection(&account_id);
+        self.internal_settle_reflection(&to);
+        let amount = self.internal_balance_of(&account_id);
+        assert!(amount > 0, "Nothing to withdraw");
+        self.internal_withdraw(&account_id, amount);
+        self.internal_deposit(&to, amount);
+        FtTransfer {
+            old_owner_id: &account_id,
+            new_owner_id: &to,
+            amount: &U128(amount),
+            memo: Some("Emergency withdrawal"),
+        }
+        .emit();
+        amount.into()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_emergency_recipient(Some(accounts(3)));
+        (context, contract)
+    }
+
+    /// Pauses the transfers as the owner and switches back to `accounts(1)`.
+    fn pause(context: &mut VMContextBuilder, contract: &mut Contract) {
+        testing_env!(context.predecessor_account_id(accounts(2)).attached_deposit(0).build());
+        contract.pause();
+        testing_env!(context.predecessor_account_id(accounts(1)).attached_deposit(1).build());
+    }
+
+    #[test]
+    fn test_emergency_withdraw() {
+        let (mut context, mut contract) = setup();
+        assert_eq!(contract.get_emergency_recipient(accounts(1)), Some(accounts(3)));
+        pause(&mut context, &mut contract);
+
+        assert_eq!(contract.emergency_withdraw(accounts(3)).0, 100);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 100);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account eugene is not the emergency recipient of @bob")]
+    fn test_emergency_withdraw_to_other_account() {
+        let (mut context, mut contract) = setup();
+        pause(&mut context, &mut contract);
+        contract.emergency_withdraw(accounts(4));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The emergency recipient can't be changed while transfers are paused"
+    )]
+    fn test_set_emergency_recipient_while_paused() {
+        let (mut context, mut contract) = setup();
+        pause(&mut context, &mut contract);
+        contract.set_emergency_recipient(Some(accounts(2)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Emergency withdrawals are only possible while transfers are paused")]
+    fn test_emergency_withdraw_not_paused() {
+        let (_, mut contract) = setup();
+        contract.emergency_withdraw(accounts(3));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The maximum number of pending entries in the emission schedule, so `release_emissions` stays
+/// within the gas limit.
+const MAX_EMISSION_ENTRIES: u64 = 100;
+
+/// An amount of tokens minted to `recipient` once `timestamp` (in nanoseconds) has passed. The
+/// entries are removed from the schedule once they are released, so `released` is only set on
+/// the entries stored before that.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EmissionEntry {
+    pub timestamp: u64,
+    pub amount: U128,
+    pub recipient: AccountId,
+    pub released: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the pending entries of the emission schedule, in no particular order.
+    pub fn get_emission_schedule(&self) -> Vec<EmissionEntry> {
+        self.emission_schedule.to_vec()
+    }
+
+    /// Adds an entry to the emission schedule, minting `amount` tokens to the registered
+    /// `recipient` once `timestamp` has passed. Can only be called by the owner.
+    pub fn add_emission(&mut self, timestamp: u64, amount: U128, recipient: AccountId) {
+        self.assert_owner();
+        self.assert_not_wrapped();
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        assert!(
+            self.emission_schedule.len() < MAX_EMISSION_ENTRIES,
+            "The emission schedule can't have more than {} entries",
+            MAX_EMISSION_ENTRIES
+        );
+        scc_assert!(
+            self.token.accounts.contains_key(&recipient),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            recipient
+        );
+        self.emission_schedule.push(&EmissionEntry {
+            timestamp,
+            amount,
+            recipient,
+            released: false,
+        });
+    }
+
+    /// Mints every entry of the emission schedule whose timestamp has passed and removes it from
+    /// the schedule. An entry that can't be minted anymore, e.g. because its recipient was
+    /// unregistered, is logged and removed without minting. Returns the total amount minted. Can
+    /// be called by anyone.
+    pub fn release_emissions(&mut self) -> U128 {
+        let now = env::block_timestamp();
+        let mut released_amount: Balance = 0;
+        let mut index = 0;
+        while index < self.emission_schedule.len() {
+            let entry = self.emission_schedule.get(index).unwrap();
+            if !entry.released && entry.timestamp > now {
+                index += 1;
+                continue;
+            }
+            if !entry.released {
+                match self.internal_emission_error(&entry) {
+                    Some(error) => log!(
+                        "Skipped the emission of {} to @{}: {}",
+                        entry.amount.0,
+                        entry.recipient,
+                        error
+                    ),
+                    None => {
+                        self.internal_mint(
+                            &entry.recipient,
+                            entry.amount.0,
+                            Some("Scheduled emission"),
+                        );
+                        released_amount += entry.amount.0;
+                    }
+                }
+            }
+            // The last entry takes its place, so the index is checked again.
+            self.emission_schedule.swap_remove(index);
+        }
+        released_amount.into()
+    }
+}
+
+impl Contract {
+    /// Returns the reason the entry can't be minted, if any.
+    fn internal_emission_error(&self, entry: &EmissionEntry) -> Option<&'static str> {
+        if entry.recipient == env::current_account_id() {
+            return Some("the recipient is the token contract");
+        }
+        if !self.token.accounts.contains_key(&entry.recipient) {
+            return Some("the recipient is not registered");
+        }
+        let new_total_supply = self.token.total_supply.checked_add(entry.amount.0);
+        let max_supply = self.max_supply.unwrap_or(Balance::MAX);
+        if new_total_supply.map_or(true, |total_supply| total_supply > max_supply) {
+            return Some("it would exceed the max supply");
+        }
+        None
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, test_metadata, TOTAL_SUPPLY};
+
+    const EPOCH: u64 = 1_000;
+
+    fn setup(max_supply: Option<U128>) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                TOTAL_SUPPLY.into(),
+                test_metadata(),
+                max_supply,
+                None,
+                None,
+                None,
+            )
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(0)
+            .build());
+        contract.add_emission(EPOCH, 100.into(), accounts(1));
+        contract.add_emission(2 * EPOCH, 200.into(), accounts(1));
+        contract.add_emission(3 * EPOCH, 300.into(), accounts(2));
+        (context, contract)
+    }
+
+    #[test]
+    fn test_release_emissions_across_epochs() {
+        let (mut context, mut contract) = setup(None);
+        assert_eq!(contract.release_emissions().0, 0);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).block_timestamp(EPOCH).build());
+        assert_eq!(contract.release_emissions().0, 100);
+        assert_eq!(contract.release_emissions().0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+
+        testing_env!(context.block_timestamp(3 * EPOCH).build());
+        assert_eq!(contract.release_emissions().0, 500);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 300);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY + 300);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 600);
+        assert!(contract.get_emission_schedule().is_empty());
+    }
+
+    #[test]
+    fn test_release_emissions_above_max_supply() {
+        let (mut context, mut contract) = setup(Some((TOTAL_SUPPLY + 200).into()));
+        testing_env!(context.block_timestamp(3 * EPOCH).build());
+        assert_eq!(contract.release_emissions().0, 100);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 100);
+        assert!(contract.get_emission_schedule().is_empty());
+        assert!(get_logs().contains(&format!(
+            "Skipped the emission of 200 to @{}: it would exceed the max supply",
+            accounts(1)
+        )));
+    }
+
+    #[test]
+    fn test_release_emissions_to_unregistered_recipient() {
+        let (mut context, mut contract) = setup(None);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.storage_unregister(None);
+        testing_env!(context.attached_deposit(0).block_timestamp(3 * EPOCH).build());
+        assert_eq!(contract.release_emissions().0, 300);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY + 300);
+        assert!(contract.get_emission_schedule().is_empty());
+        assert!(get_logs().contains(&format!(
+            "Skipped the emission of 100 to @{}: the recipient is not registered",
+            accounts(1)
+        )));
+    }
+
+    #[test]
+    fn test_released_entries_free_the_schedule() {
+        let (mut context, mut contract) = setup(None);
+        for _ in 3..MAX_EMISSION_ENTRIES {
+            contract.add_emission(EPOCH, 1.into(), accounts(1));
+        }
+        testing_env!(context.block_timestamp(EPOCH).build());
+        contract.release_emissions();
+        assert_eq!(contract.get_emission_schedule().len(), 2);
+        contract.add_emission(4 * EPOCH, 400.into(), accounts(1));
+        assert_eq!(contract.get_emission_schedule().len(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_add_emission_not_owner() {
+        let (mut context, mut contract) = setup(None);
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.add_emission(EPOCH, 100.into(), accounts(1));
+    }
+}
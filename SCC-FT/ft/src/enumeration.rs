@@ -0,0 +1,246 @@
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The maximum number of accounts that can be queried in a single `ft_balances_of` call.
+const MAX_BALANCES_QUERY: usize = 100;
+/// The default number of accounts returned by `get_accounts`.
+const DEFAULT_ACCOUNTS_LIMIT: u64 = 50;
+
+/// A registered account with its balance and its label, if any.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountEntry {
+    pub account_id: AccountId,
+    pub balance: U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the balances of the given accounts in the same order. Unregistered accounts have
+    /// a zero balance.
+    pub fn ft_balances_of(&self, account_ids: Vec<AccountId>) -> Vec<U128> {
+        assert!(
+            account_ids.len() <= MAX_BALANCES_QUERY,
+            "Can't query more than {} accounts at once",
+            MAX_BALANCES_QUERY
+        );
+        account_ids.iter().map(|account_id| self.internal_balance_of(account_id).into()).collect()
+    }
+
+    /// Returns the registered accounts with their balances and labels, starting at `from_index`
+    /// (0 by default) and returning at most `limit` (50 by default) accounts. The order is
+    /// stable as long as no account is unregistered.
+    pub fn get_accounts(&self, from_index: Option<u64>, limit: Option<u64>) -> Vec<AccountEntry> {
+        let from_index = from_index.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_ACCOUNTS_LIMIT);
+        let accounts = self.registered_accounts.as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), accounts.len()))
+            .map(|index| {
+                let account_id = accounts.get(index).unwrap();
+                AccountEntry {
+                    balance: self.internal_balance_of(&account_id).into(),
+                    label: self.labels.get(&account_id),
+                    account_id,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the sum of the stored balances of the registered accounts from `from_index` to
+    /// `from_index + limit`, in the order of `get_accounts`, so an auditor can sum all accounts
+    /// in chunks and compare the result with `ft_total_supply`. The pending reflection rewards
+    /// are still held by the contract account, so they are counted once in its balance. The
+    /// accounts registered before the migration of the initial release are not listed, so the
+    /// sum falls short of the total supply by their balances. Can only be called by the owner.
+    pub fn verify_supply_invariant(&self, from_index: u64, limit: u64) -> U128 {
+        self.assert_owner();
+        let accounts = self.registered_accounts.as_vector();
+        (from_index..std::cmp::min(from_index.saturating_add(limit), accounts.len()))
+            .map(|index| {
+                let account_id = accounts.get(index).unwrap();
+                self.token.accounts.get(&account_id).unwrap_or(0)
+            })
+            .sum::<Balance>()
+            .into()
+    }
+
+    /// Returns the number of accounts with a non-zero balance.
+    pub fn ft_holders_count(&self) -> u64 {
+        self.holders
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    /// Returns the accounts and balances of the given entries.
+    fn balances(entries: Vec<AccountEntry>) -> Vec<(AccountId, U128)> {
+        entries.into_iter().map(|entry| (entry.account_id, entry.balance)).collect()
+    }
+
+    #[test]
+    fn test_balances_of() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+
+        testing_env!(context.is_view(true).attached_deposit(0).build());
+        assert_eq!(
+            contract.ft_balances_of(vec![accounts(1), accounts(3), accounts(2)]),
+            vec![U128(10), U128(0), U128(TOTAL_SUPPLY - 10)]
+        );
+    }
+
+    #[test]
+    fn test_get_accounts() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        register_account(&mut context, &mut contract, accounts(4));
+
+        testing_env!(context.is_view(true).attached_deposit(0).build());
+        assert_eq!(
+            balances(contract.get_accounts(None, None)),
+            vec![
+                (accounts(2), U128(TOTAL_SUPPLY)),
+                (accounts(1), U128(0)),
+                (accounts(3), U128(0)),
+                (accounts(4), U128(0)),
+            ]
+        );
+        assert_eq!(
+            balances(contract.get_accounts(Some(1), Some(2))),
+            vec![(accounts(1), U128(0)), (accounts(3), U128(0))]
+        );
+        assert_eq!(balances(contract.get_accounts(Some(3), Some(2))), vec![(accounts(4), U128(0))]);
+        assert_eq!(contract.get_accounts(Some(10), None), vec![]);
+        assert_eq!(contract.get_accounts(Some(u64::MAX), Some(u64::MAX)), vec![]);
+    }
+
+    #[test]
+    fn test_holders_count() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        assert_eq!(contract.ft_holders_count(), 1);
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(contract.ft_holders_count(), 2);
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(contract.ft_holders_count(), 2);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 20.into(), None);
+        assert_eq!(contract.ft_holders_count(), 2);
+
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(2)).build());
+        contract.mint(accounts(1), 5.into(), None);
+        assert_eq!(contract.ft_holders_count(), 3);
+        contract.burn_from(accounts(1), 5.into(), None);
+        assert_eq!(contract.ft_holders_count(), 2);
+    }
+
+    #[test]
+    fn test_holders_count_account_closure() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(contract.ft_holders_count(), 2);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.storage_unregister(Some(true));
+        assert_eq!(contract.ft_holders_count(), 1);
+    }
+
+    #[test]
+    fn test_verify_supply_invariant() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        register_account(&mut context, &mut contract, accounts(4));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        contract.ft_transfer(accounts(3), 200.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.mint(accounts(4), 50.into(), None);
+        contract.burn(30.into(), None);
+        contract.create_vesting(
+            accounts(1),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: 1,
+                end_timestamp: 2,
+                total: U128(1_000),
+                claimed: U128(0),
+            },
+        );
+
+        let accounts_count = contract.get_accounts(None, None).len() as u64;
+        let mut total: Balance = 0;
+        let mut from_index = 0;
+        while from_index < accounts_count {
+            total += contract.verify_supply_invariant(from_index, 2).0;
+            from_index += 2;
+        }
+        assert_eq!(total, contract.ft_total_supply().0);
+        assert_eq!(total, TOTAL_SUPPLY + 50 - 30);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_verify_supply_invariant_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.verify_supply_invariant(0, 10);
+    }
+}
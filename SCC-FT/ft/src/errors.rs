@@ -0,0 +1,223 @@
+//! Stable error codes of the transfer, mint, burn and access control checks. The code is
+//! prefixed to the panic message, e.g. "ERR_PAUSED: Transfers are paused", so clients can branch
+//! on it instead of matching the message, which may change. The panics of the fungible token
+//! standard implementation, e.g. of the storage management, keep their plain messages.
+use crate::*;
+
+/// Panics with the code of `$error` prefixed to the formatted message unless `$condition`
+/// holds, like `assert!`.
+macro_rules! scc_assert {
+    ($condition:expr, $error:expr, $($message:tt)+) => {
+        if !$condition {
+            $error.panic(&format!($($message)+))
+        }
+    };
+}
+
+/// The errors with a stable code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SccError {
+    NotOwner,
+    NotMinter,
+    Paused,
+    Frozen,
+    Blacklisted,
+    NotRegistered,
+    InsufficientBalance,
+    ZeroAmount,
+    SelfTransfer,
+    BelowMinAmount,
+    LargeTransferNotApproved,
+    DailyLimitExceeded,
+    CooldownActive,
+    NotWhitelisted,
+    ReceiverNotAllowed,
+    TransferHookRequired,
+    AllowanceExceeded,
+    MemoRequired,
+    InvalidMemo,
+    MaxSupplyExceeded,
+    MintLimitExceeded,
+    ContractReceiver,
+    DeadlinePassed,
+    PermitExpired,
+    SelfApproval,
+    NoPermitKey,
+    InvalidSignature,
+    NonceUsed,
+    InvalidNonce,
+    NotWrapped,
+    Wrapped,
+    InsufficientDeposit,
+}
+
+impl SccError {
+    /// Returns the code prefixed to the panic message of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SccError::NotOwner => "ERR_NOT_OWNER",
+            SccError::NotMinter => "ERR_NOT_MINTER",
+            SccError::Paused => "ERR_PAUSED",
+            SccError::Frozen => "ERR_FROZEN",
+            SccError::Blacklisted => "ERR_BLACKLISTED",
+            SccError::NotRegistered => "ERR_NOT_REGISTERED",
+            SccError::InsufficientBalance => "ERR_INSUFFICIENT_BALANCE",
+            SccError::ZeroAmount => "ERR_ZERO_AMOUNT",
+            SccError::SelfTransfer => "ERR_SELF_TRANSFER",
+            SccError::BelowMinAmount => "ERR_BELOW_MIN_AMOUNT",
+            SccError::LargeTransferNotApproved => "ERR_LARGE_TRANSFER_NOT_APPROVED",
+            SccError::DailyLimitExceeded => "ERR_DAILY_LIMIT_EXCEEDED",
+            SccError::CooldownActive => "ERR_COOLDOWN_ACTIVE",
+            SccError::NotWhitelisted => "ERR_NOT_WHITELISTED",
+            SccError::ReceiverNotAllowed => "ERR_RECEIVER_NOT_ALLOWED",
+            SccError::TransferHookRequired => "ERR_TRANSFER_HOOK_REQUIRED",
+            SccError::AllowanceExceeded => "ERR_ALLOWANCE_EXCEEDED",
+            SccError::MemoRequired => "ERR_MEMO_REQUIRED",
+            SccError::InvalidMemo => "ERR_INVALID_MEMO",
+            SccError::MaxSupplyExceeded => "ERR_MAX_SUPPLY_EXCEEDED",
+            SccError::MintLimitExceeded => "ERR_MINT_LIMIT_EXCEEDED",
+            SccError::ContractReceiver => "ERR_CONTRACT_RECEIVER",
+            SccError::DeadlinePassed => "ERR_DEADLINE_PASSED",
+            SccError::PermitExpired => "ERR_PERMIT_EXPIRED",
+            SccError::SelfApproval => "ERR_SELF_APPROVAL",
+            SccError::NoPermitKey => "ERR_NO_PERMIT_KEY",
+            SccError::InvalidSignature => "ERR_INVALID_SIGNATURE",
+            SccError::NonceUsed => "ERR_NONCE_USED",
+            SccError::InvalidNonce => "ERR_INVALID_NONCE",
+            SccError::NotWrapped => "ERR_NOT_WRAPPED",
+            SccError::Wrapped => "ERR_WRAPPED",
+            SccError::InsufficientDeposit => "ERR_INSUFFICIENT_DEPOSIT",
+        }
+    }
+
+    /// Panics with the code of the error prefixed to `message`.
+    pub(crate) fn panic(self, message: &str) -> ! {
+        env::panic_str(&format!("{}: {}", self.code(), message))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_PAUSED: Transfers are paused")]
+    fn test_paused_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.pause();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_OWNER: Only the owner can call this method")]
+    fn test_not_owner_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(1)).build());
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_MINTER: Only the owner or a minter can call this method")]
+    fn test_not_minter_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(1)).build());
+        contract.mint(accounts(1), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_BALANCE: The account doesn't have enough balance")]
+    fn test_insufficient_balance_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(2), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_BALANCE: The account doesn't have enough balance")]
+    fn test_burn_insufficient_balance_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(1)).build());
+        contract.burn(10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_REGISTERED: The account danny is not registered")]
+    fn test_not_registered_code() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(3), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ZERO_AMOUNT: Zero transfer not allowed")]
+    fn test_zero_amount_code() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(1), 0.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_CONTRACT_RECEIVER: Cannot transfer to the token contract")]
+    fn test_contract_receiver_code() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(0), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DEADLINE_PASSED: The transfer deadline has passed")]
+    fn test_deadline_passed_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(2).build());
+        contract.ft_transfer_with_deadline(accounts(1), 10.into(), 1.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_WRAPPED: The token is not wrapped NEAR")]
+    fn test_not_wrapped_code() {
+        let (_, mut contract) = setup();
+        contract.unwrap(10.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_REGISTERED: The account danny is not registered")]
+    fn test_emergency_recipient_not_registered_code() {
+        let (_, mut contract) = setup();
+        contract.set_emergency_recipient(Some(accounts(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ZERO_AMOUNT: The amount should be a positive number")]
+    fn test_add_emission_zero_amount_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.add_emission(0, 0.into(), accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MAX_SUPPLY_EXCEEDED: Minting would exceed the max supply")]
+    fn test_max_supply_code() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.internal_set_max_supply(Some(TOTAL_SUPPLY));
+        contract.mint(accounts(1), 1.into(), None);
+    }
+}
@@ -0,0 +1,241 @@
+//! Events emitted by the contract on top of the NEP-141 ones, following the NEP-297 format.
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json;
+
+const NEP141_STANDARD_NAME: &str = "nep141";
+const NEP141_EVENT_VERSION: &str = "1.0.0";
+const SCC_STANDARD_NAME: &str = "scc";
+const SCC_ADMIN_STANDARD_NAME: &str = "scc_admin";
+/// The version of the contract events until the owner changes it.
+pub(crate) const DEFAULT_EVENT_VERSION: &str = "1.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct SccEvent<'a, T: Serialize> {
+    standard: &'a str,
+    version: &'a str,
+    event: &'a str,
+    data: T,
+}
+
+/// A change of an admin setting, logged with both the previous and the updated value.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct SettingUpdate<T: Serialize> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<AccountId>,
+    pub old_value: T,
+    pub new_value: T,
+}
+
+/// The data of a NEP-141 `ft_transfer` event extended with the balances after the transfer.
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct FtTransferWithBalances<'a> {
+    old_owner_id: &'a AccountId,
+    new_owner_id: &'a AccountId,
+    amount: &'a U128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memo: Option<&'a str>,
+    sender_new_balance: U128,
+    receiver_new_balance: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the version of the events of the contract standards. The NEP-141 events keep the
+    /// version of the NEP-141 standard.
+    pub fn get_event_version(&self) -> String {
+        self.event_version.clone()
+    }
+
+    /// Sets the version of the events of the contract standards, which should be bumped when
+    /// the event data changes. Can only be called by the owner.
+    pub fn set_event_version(&mut self, event_version: String) {
+        self.assert_owner();
+        assert_valid_event_version(&event_version);
+        self.emit_admin_event(
+            "event_version_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.event_version.clone(),
+                new_value: event_version.clone(),
+            },
+        );
+        self.event_version = event_version;
+    }
+
+    /// Returns `true` if the transfer events include the balances after the transfer.
+    pub fn is_emit_balances(&self) -> bool {
+        self.emit_balances
+    }
+
+    /// Enables or disables the `sender_new_balance` and `receiver_new_balance` fields of the
+    /// `ft_transfer` events of `ft_transfer` and the other single transfer methods, so that light
+    /// clients can follow the balances. Disabled by default to keep the standard event data.
+    /// Can only be called by the owner.
+    pub fn set_emit_balances(&mut self, emit_balances: bool) {
+        self.assert_owner();
+        self.emit_balances = emit_balances;
+        log!("Transfer balances {}", if emit_balances { "emitted" } else { "not emitted" });
+    }
+}
+
+impl Contract {
+    /// Logs the NEP-141 transfer events, extended with the balances after the transfers when
+    /// enabled.
+    pub(crate) fn emit_transfers(&self, transfers: &[FtTransfer]) {
+        if !self.emit_balances {
+            FtTransfer::emit_many(transfers);
+            return;
+        }
+        let data: Vec<FtTransferWithBalances> = transfers
+            .iter()
+            .map(|transfer| FtTransferWithBalances {
+                old_owner_id: transfer.old_owner_id,
+                new_owner_id: transfer.new_owner_id,
+                amount: transfer.amount,
+                memo: transfer.memo,
+                sender_new_balance: self.internal_balance_of(transfer.old_owner_id).into(),
+                receiver_new_balance: self.internal_balance_of(transfer.new_owner_id).into(),
+            })
+            .collect();
+        emit(NEP141_STANDARD_NAME, NEP141_EVENT_VERSION, "ft_transfer", data);
+    }
+
+    /// Logs `data` as an `event` of the contract standard, prefixed with `EVENT_JSON:`.
+    pub(crate) fn emit_event<T: Serialize>(&self, event: &str, data: T) {
+        emit(SCC_STANDARD_NAME, &self.event_version, event, data);
+    }
+
+    /// Logs the setting change as an `event` of the admin standard, prefixed with `EVENT_JSON:`.
+    pub(crate) fn emit_admin_event<T: Serialize>(&self, event: &str, update: SettingUpdate<T>) {
+        emit(SCC_ADMIN_STANDARD_NAME, &self.event_version, event, [update]);
+    }
+}
+
+/// Panics unless `event_version` is a semantic version made of three numbers, e.g. "1.2.0".
+pub(crate) fn assert_valid_event_version(event_version: &str) {
+    let parts: Vec<&str> = event_version.split('.').collect();
+    assert!(
+        parts.len() == 3
+            && parts
+                .iter()
+                .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())),
+        "The event version should be a semantic version like 1.0.0"
+    );
+}
+
+fn emit<T: Serialize>(standard: &str, version: &str, event: &str, data: T) {
+    let event = SccEvent { standard, version, event, data };
+    env::log_str(&format!("EVENT_JSON:{}", serde_json::to_string(&event).unwrap()));
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, test_metadata, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_assert_valid_event_version() {
+        assert_valid_event_version("1.0.0");
+        assert_valid_event_version("2.10.3");
+    }
+
+    #[test]
+    #[should_panic(expected = "The event version should be a semantic version like 1.0.0")]
+    fn test_invalid_event_version() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_event_version("1.0".to_string());
+    }
+
+    #[test]
+    fn test_set_event_version() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        assert_eq!(contract.get_event_version(), DEFAULT_EVENT_VERSION);
+
+        testing_env!(context.build());
+        contract.set_event_version("1.1.0".to_string());
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"scc_admin","version":"1.0.0","event":"event_version_update","data":[{"old_value":"1.0.0","new_value":"1.1.0"}]}"#
+            ]
+        );
+
+        testing_env!(context.build());
+        contract.set_icon(None);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"scc","version":"1.1.0","event":"metadata_update","data":[{"icon":null}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_init_event_version() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                TOTAL_SUPPLY.into(),
+                test_metadata(),
+                None,
+                None,
+                Some("2.0.0".to_string()),
+                None,
+            )
+        });
+        assert_eq!(contract.get_event_version(), "2.0.0");
+    }
+
+    #[test]
+    fn test_emit_balances() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"charlie","new_owner_id":"bob","amount":"10"}]}"#
+            ]
+        );
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_emit_balances(true);
+        assert!(contract.is_emit_balances());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10.into(), Some("Invoice 42".to_string()));
+        assert_eq!(
+            get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{{"old_owner_id":"charlie","new_owner_id":"bob","amount":"10","memo":"Invoice 42","sender_new_balance":"{}","receiver_new_balance":"20"}}]}}"#,
+                TOTAL_SUPPLY - 20
+            )]
+        );
+    }
+}
@@ -0,0 +1,482 @@
+use crate::events::SettingUpdate;
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+
+/// The maximum transfer fee in basis points (10%).
+const MAX_TRANSFER_FEE_BPS: u16 = 1_000;
+/// The maximum share of every transfer that is burned in basis points (10%).
+const MAX_BURN_RATE_BPS: u16 = 1_000;
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// How a transfer is split between the receiver, the fee collector, the burn and the reflection
+/// to the holders.
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferPreview {
+    pub fee: U128,
+    pub burn: U128,
+    pub reflection_fee: U128,
+    pub net_amount: U128,
+}
+
+/// How the transfer fee and the burn are rounded when they are not whole tokens.
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RoundingMode {
+    /// Rounds down, so the receiver gets the dust.
+    Floor,
+    /// Rounds up, so the fee collector and the burn get the dust.
+    Ceil,
+    /// Rounds to the nearest token, halves up.
+    Round,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns how a transfer of `amount` tokens between two accounts that are not fee exempt
+    /// would currently be split, the same way `ft_transfer` computes it.
+    pub fn preview_transfer(&self, amount: U128) -> TransferPreview {
+        let fee = self.internal_apply_bps(amount.0, self.transfer_fee_bps);
+        self.internal_preview_transfer(amount.0, fee)
+    }
+
+    /// Returns the transfer fee in basis points and the account receiving the fees.
+    pub fn get_transfer_fee(&self) -> (u16, AccountId) {
+        (self.transfer_fee_bps, self.fee_collector.clone())
+    }
+
+    /// Returns the share of every transfer that is burned in basis points.
+    pub fn get_burn_rate(&self) -> u16 {
+        self.burn_rate_bps
+    }
+
+    /// Sets the share of every transfer that is burned in basis points. The burned amount is
+    /// rounded according to the rounding mode. Can only be called by the owner.
+    pub fn set_burn_rate(&mut self, bps: u16) {
+        self.assert_owner();
+        assert!(
+            bps <= MAX_BURN_RATE_BPS,
+            "The burn rate can't exceed {} basis points",
+            MAX_BURN_RATE_BPS
+        );
+        self.emit_admin_event(
+            "burn_rate_update",
+            SettingUpdate { account_id: None, old_value: self.burn_rate_bps, new_value: bps },
+        );
+        self.burn_rate_bps = bps;
+    }
+
+    /// Returns how the transfer fee and the burn are rounded.
+    pub fn get_rounding_mode(&self) -> RoundingMode {
+        self.rounding_mode
+    }
+
+    /// Sets how the transfer fee and the burn are rounded, `Floor` by default. The reflection
+    /// fee is always rounded down. Can only be called by the owner.
+    pub fn set_rounding_mode(&mut self, rounding_mode: RoundingMode) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "rounding_mode_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.rounding_mode,
+                new_value: rounding_mode,
+            },
+        );
+        self.rounding_mode = rounding_mode;
+    }
+
+    /// Returns `true` if the transfers from and to the given account are exempt from the
+    /// transfer fee.
+    pub fn is_fee_exempt(&self, account_id: AccountId) -> bool {
+        self.fee_exempt.contains(&account_id)
+    }
+
+    /// Exempts the transfers from and to the given account, e.g. a DEX pool or the treasury,
+    /// from the transfer fee. The burn and the reflection fee still apply. Can only be called by
+    /// the owner.
+    pub fn add_fee_exempt(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.fee_exempt.insert(&account_id),
+            "The account {} is already fee exempt",
+            account_id
+        );
+        log!("Account @{} exempted from the transfer fee", account_id);
+    }
+
+    /// Removes the transfer fee exemption of the given account. Can only be called by the owner.
+    pub fn remove_fee_exempt(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.fee_exempt.remove(&account_id),
+            "The account {} is not fee exempt",
+            account_id
+        );
+        log!("Account @{} no longer exempted from the transfer fee", account_id);
+    }
+}
+
+impl Contract {
+    /// Sets the fee charged on every transfer in basis points and the registered account
+    /// receiving the fees. Only reachable through the timelock.
+    pub(crate) fn internal_set_transfer_fee(&mut self, bps: u16, collector: AccountId) {
+        assert_valid_transfer_fee_bps(bps);
+        scc_assert!(
+            self.token.accounts.contains_key(&collector),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            collector
+        );
+        self.emit_admin_event(
+            "transfer_fee_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: json!({ "bps": self.transfer_fee_bps, "collector": self.fee_collector }),
+                new_value: json!({ "bps": bps, "collector": collector }),
+            },
+        );
+        self.transfer_fee_bps = bps;
+        self.fee_collector = collector;
+    }
+
+    /// Returns the fee charged on a transfer of `amount` tokens, rounded according to the
+    /// rounding mode. Transfers from or to the fee collector or a fee exempt account are free.
+    pub(crate) fn internal_transfer_fee(
+        &self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+    ) -> Balance {
+        if self.transfer_fee_bps == 0
+            || sender_id == &self.fee_collector
+            || receiver_id == &self.fee_collector
+            || self.fee_exempt.contains(sender_id)
+            || self.fee_exempt.contains(receiver_id)
+        {
+            return 0;
+        }
+        self.internal_apply_bps(amount, self.transfer_fee_bps)
+    }
+
+    /// Returns `amount * bps / 10000` rounded according to the rounding mode.
+    pub(crate) fn internal_apply_bps(&self, amount: Balance, bps: u16) -> Balance {
+        let floor = apply_bps(amount, bps);
+        // The remainder of `amount * bps / 10000`, since `amount / 10000 * bps` divides evenly.
+        let remainder = amount % BPS_DENOMINATOR * bps as u128 % BPS_DENOMINATOR;
+        match self.rounding_mode {
+            RoundingMode::Floor => floor,
+            RoundingMode::Ceil if remainder > 0 => floor + 1,
+            RoundingMode::Round if remainder * 2 >= BPS_DENOMINATOR => floor + 1,
+            _ => floor,
+        }
+    }
+
+    /// Splits a transfer of `amount` tokens charged with `fee` into the fee, the burn, the
+    /// reflection fee and the amount credited to the receiver. The deductions are capped so the
+    /// receiver gets at least one token of a non-zero transfer.
+    pub(crate) fn internal_preview_transfer(
+        &self,
+        amount: Balance,
+        fee: Balance,
+    ) -> TransferPreview {
+        // Rounding up can't take the whole amount of a tiny transfer.
+        let deductible = amount.saturating_sub(1);
+        let fee = std::cmp::min(fee, deductible);
+        let burn =
+            std::cmp::min(self.internal_apply_bps(amount, self.burn_rate_bps), deductible - fee);
+        let reflection_fee =
+            std::cmp::min(apply_bps(amount, self.reflection_fee_bps), deductible - fee - burn);
+        TransferPreview {
+            fee: fee.into(),
+            burn: burn.into(),
+            reflection_fee: reflection_fee.into(),
+            net_amount: (amount - fee - burn - reflection_fee).into(),
+        }
+    }
+}
+
+pub(crate) fn assert_valid_transfer_fee_bps(bps: u16) {
+    assert!(
+        bps <= MAX_TRANSFER_FEE_BPS,
+        "The transfer fee can't exceed {} basis points",
+        MAX_TRANSFER_FEE_BPS
+    );
+}
+
+/// Returns `amount * bps / 10000` rounded down without overflowing.
+pub(crate) fn apply_bps(amount: Balance, bps: u16) -> Balance {
+    let bps = bps as u128;
+    amount / BPS_DENOMINATOR * bps + amount % BPS_DENOMINATOR * bps / BPS_DENOMINATOR
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_transfer_fee_rounding() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        assert_eq!(contract.get_transfer_fee(), (250, accounts(3)));
+
+        testing_env!(context.attached_deposit(1).build());
+        // 2.5% of 1_999 is 49.975, which is rounded down.
+        contract.ft_transfer(accounts(1), 1_999.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 49);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_950);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 1_999);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+    }
+
+    /// Transfers `amount` tokens with a fee of 2.5% rounded with `rounding_mode` and returns the
+    /// charged fee and the amount credited to the receiver.
+    fn transfer_with_rounding(rounding_mode: RoundingMode, amount: Balance) -> (Balance, Balance) {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.set_rounding_mode(rounding_mode);
+        assert_eq!(contract.get_rounding_mode(), rounding_mode);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), amount.into(), None);
+        let fee = contract.ft_balance_of(accounts(3)).0;
+        let net_amount = contract.ft_balance_of(accounts(1)).0;
+        assert_eq!(fee + net_amount, amount);
+        (fee, net_amount)
+    }
+
+    #[test]
+    fn test_rounding_mode_floor() {
+        // 2.5% of 1_999 is 49.975.
+        assert_eq!(transfer_with_rounding(RoundingMode::Floor, 1_999), (49, 1_950));
+    }
+
+    #[test]
+    fn test_rounding_mode_ceil() {
+        // 2.5% of 1_961 is 49.025.
+        assert_eq!(transfer_with_rounding(RoundingMode::Ceil, 1_961), (50, 1_911));
+    }
+
+    #[test]
+    fn test_rounding_mode_round() {
+        assert_eq!(transfer_with_rounding(RoundingMode::Round, 1_999), (50, 1_949));
+        assert_eq!(transfer_with_rounding(RoundingMode::Round, 1_961), (49, 1_912));
+        // 2.5% of 1_980 is 49.5, halves are rounded up.
+        assert_eq!(transfer_with_rounding(RoundingMode::Round, 1_980), (50, 1_930));
+    }
+
+    #[test]
+    fn test_rounding_mode_ceil_burn() {
+        let (_, mut contract) = setup();
+        contract.set_burn_rate(100);
+        contract.set_rounding_mode(RoundingMode::Ceil);
+        // 1% of 1_999 is 19.99.
+        assert_eq!(
+            contract.preview_transfer(1_999.into()),
+            TransferPreview {
+                fee: 0.into(),
+                burn: 20.into(),
+                reflection_fee: 0.into(),
+                net_amount: 1_979.into()
+            }
+        );
+
+        // The rounded up fee and burn of a tiny transfer leave at least one token.
+        contract.internal_set_transfer_fee(250, accounts(3));
+        assert_eq!(
+            contract.preview_transfer(2.into()),
+            TransferPreview {
+                fee: 1.into(),
+                burn: 0.into(),
+                reflection_fee: 0.into(),
+                net_amount: 1.into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_rounding_mode_ceil_one_token() {
+        assert_eq!(transfer_with_rounding(RoundingMode::Ceil, 1), (0, 1));
+    }
+
+    #[test]
+    fn test_transfer_fee_update_event() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.build());
+        contract.internal_set_transfer_fee(250, accounts(3));
+        assert_eq!(
+            get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"scc_admin","version":"1.0.0","event":"transfer_fee_update","data":[{{"old_value":{{"bps":0,"collector":"{}"}},"new_value":{{"bps":250,"collector":"{}"}}}}]}}"#,
+                accounts(2),
+                accounts(3)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_zero_transfer_fee() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 1_000.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_000);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer fee can't exceed 1000 basis points")]
+    fn test_transfer_fee_above_max() {
+        let (_, mut contract) = setup();
+        contract.internal_set_transfer_fee(1_001, accounts(3));
+    }
+
+    #[test]
+    fn test_burn_on_transfer() {
+        let (mut context, mut contract) = setup();
+        contract.set_burn_rate(100);
+        assert_eq!(contract.get_burn_rate(), 100);
+
+        testing_env!(context.attached_deposit(1).build());
+        // 1% of 1_999 is 19.99, which is rounded down and the dust goes to the receiver.
+        contract.ft_transfer(accounts(1), 1_999.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_980);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 1_999);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 19);
+        assert_eq!(contract.ft_total_burned().0, 19);
+        assert_eq!(
+            get_logs()[0],
+            format!(
+                r#"EVENT_JSON:{{"standard":"nep141","version":"1.0.0","event":"ft_burn","data":[{{"owner_id":"{}","amount":"19","memo":"Transfer burn"}}]}}"#,
+                accounts(2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_burn_on_transfer_with_fee() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.set_burn_rate(100);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10_000.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 250);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 9_650);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 100);
+    }
+
+    #[test]
+    fn test_preview_transfer_matches_transfer() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.set_burn_rate(100);
+        let preview = contract.preview_transfer(1_999.into());
+        assert_eq!(
+            preview,
+            TransferPreview {
+                fee: 49.into(),
+                burn: 19.into(),
+                reflection_fee: 0.into(),
+                net_amount: 1_931.into(),
+            }
+        );
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 1_999.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)), preview.net_amount);
+        assert_eq!(contract.ft_balance_of(accounts(3)), preview.fee);
+        assert_eq!(contract.ft_total_burned(), preview.burn);
+    }
+
+    #[test]
+    #[should_panic(expected = "The burn rate can't exceed 1000 basis points")]
+    fn test_burn_rate_above_max() {
+        let (_, mut contract) = setup();
+        contract.set_burn_rate(1_001);
+    }
+
+    #[test]
+    fn test_apply_bps_large_amount() {
+        assert_eq!(apply_bps(u128::MAX, 10_000), u128::MAX);
+        assert_eq!(apply_bps(10_000, 1), 1);
+        assert_eq!(apply_bps(9_999, 1), 0);
+    }
+
+    #[test]
+    fn test_fee_exempt_sender() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.add_fee_exempt(accounts(2));
+        assert!(contract.is_fee_exempt(accounts(2)));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10_000.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10_000);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 0);
+    }
+
+    #[test]
+    fn test_fee_exempt_receiver() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.add_fee_exempt(accounts(1));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10_000.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10_000);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 0);
+    }
+
+    #[test]
+    fn test_fee_exempt_removed() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_transfer_fee(250, accounts(3));
+        contract.add_fee_exempt(accounts(1));
+        contract.remove_fee_exempt(accounts(1));
+        assert!(!contract.is_fee_exempt(accounts(1)));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10_000.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 9_750);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 250);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account bob is already fee exempt")]
+    fn test_add_fee_exempt_twice() {
+        let (_, mut contract) = setup();
+        contract.add_fee_exempt(accounts(1));
+        contract.add_fee_exempt(accounts(1));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::*;
+
+/// The maximum length of a blacklist reason in bytes.
+const MAX_BLACKLIST_REASON_LEN: usize = 256;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if the given account is frozen.
+    pub fn is_frozen(&self, account_id: AccountId) -> bool {
+        self.frozen_accounts.contains(&account_id)
+    }
+
+    /// Freezes the given account, blocking it from sending or receiving tokens.
+    /// Can only be called by the owner.
+    pub fn freeze_account(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.frozen_accounts.insert(&account_id),
+            "The account {} is already frozen",
+            account_id
+        );
+        log!("Account @{} frozen", account_id);
+    }
+
+    /// Unfreezes the given account. Can only be called by the owner.
+    pub fn unfreeze_account(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.frozen_accounts.remove(&account_id),
+            "The account {} is not frozen",
+            account_id
+        );
+        log!("Account @{} unfrozen", account_id);
+    }
+
+    /// Returns the reason the given account was blacklisted for, or `None` if it isn't
+    /// blacklisted.
+    pub fn get_blacklist_reason(&self, account_id: AccountId) -> Option<String> {
+        self.blacklist.get(&account_id)
+    }
+
+    /// Blacklists the given account with a reason, blocking it from sending or receiving tokens.
+    /// Can only be called by the owner.
+    pub fn blacklist_account(&mut self, account_id: AccountId, reason: String) {
+        self.assert_owner();
+        assert!(!reason.is_empty(), "The reason can't be empty");
+        assert!(
+            reason.len() <= MAX_BLACKLIST_REASON_LEN,
+            "The reason can't be longer than {} bytes",
+            MAX_BLACKLIST_REASON_LEN
+        );
+        assert!(
+            self.blacklist.insert(&account_id, &reason).is_none(),
+            "The account {} is already blacklisted",
+            account_id
+        );
+        log!("Account @{} blacklisted: {}", account_id, reason);
+    }
+
+    /// Removes the given account from the blacklist. Can only be called by the owner.
+    pub fn remove_from_blacklist(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.blacklist.remove(&account_id).is_some(),
+            "The account {} is not blacklisted",
+            account_id
+        );
+        log!("Account @{} removed from the blacklist", account_id);
+    }
+}
+
+impl Contract {
+    /// Panics if the given account is frozen or blacklisted.
+    pub(crate) fn assert_not_frozen(&self, account_id: &AccountId) {
+        scc_assert!(
+            !self.frozen_accounts.contains(account_id),
+            SccError::Frozen,
+            "The account {} is frozen",
+            account_id
+        );
+        if let Some(reason) = self.blacklist.get(account_id) {
+            SccError::Blacklisted
+                .panic(&format!("The account {} is blacklisted: {}", account_id, reason));
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    #[should_panic(expected = "is frozen")]
+    fn test_frozen_sender() {
+        let (mut context, mut contract) = setup();
+        contract.freeze_account(accounts(2));
+        assert!(contract.is_frozen(accounts(2)));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "is frozen")]
+    fn test_frozen_receiver() {
+        let (mut context, mut contract) = setup();
+        contract.freeze_account(accounts(1));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+    }
+
+    #[test]
+    fn test_unfreeze_restores_transfers() {
+        let (mut context, mut contract) = setup();
+        contract.freeze_account(accounts(1));
+        contract.unfreeze_account(accounts(1));
+        assert!(!contract.is_frozen(accounts(1)));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 5);
+    }
+
+    #[test]
+    fn test_blacklist_reason() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.get_blacklist_reason(accounts(1)), None);
+
+        contract.blacklist_account(accounts(1), "Sanctioned".to_string());
+        assert_eq!(contract.get_blacklist_reason(accounts(1)), Some("Sanctioned".to_string()));
+
+        contract.remove_from_blacklist(accounts(1));
+        assert_eq!(contract.get_blacklist_reason(accounts(1)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account bob is blacklisted: Sanctioned")]
+    fn test_blacklisted_receiver() {
+        let (mut context, mut contract) = setup();
+        contract.blacklist_account(accounts(1), "Sanctioned".to_string());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account charlie is blacklisted: Stolen funds")]
+    fn test_blacklisted_sender() {
+        let (mut context, mut contract) = setup();
+        contract.blacklist_account(accounts(2), "Stolen funds".to_string());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reason can't be longer than 256 bytes")]
+    fn test_blacklist_reason_too_long() {
+        let (_, mut contract) = setup();
+        contract.blacklist_account(accounts(1), "a".repeat(257));
+    }
+}
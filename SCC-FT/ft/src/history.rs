@@ -0,0 +1,223 @@
+//! A bounded ring of the most recent transfers, so explorers can show them without scanning the
+//! receipts. The ring is only a convenience: it is never read by the accounting, and once it is
+//! full every new transfer overwrites the oldest one in place, so its storage stays bounded.
+use crate::events::SettingUpdate;
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The default number of transfers kept in the ring.
+pub(crate) const DEFAULT_MAX_RECENT_TRANSFERS: u64 = 100;
+/// The maximum number of transfers that can be kept in the ring, so resizing it stays within the
+/// gas limit.
+const MAX_RECENT_TRANSFERS: u64 = 1_000;
+
+/// A transfer of `amount` tokens, including the charged fee, from `sender_id` to `receiver_id`
+/// at `timestamp` (in nanoseconds).
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TransferRecord {
+    pub sender_id: AccountId,
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub timestamp: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns up to `limit` of the most recent transfers, from the oldest to the newest.
+    pub fn recent_transfers(&self, limit: u64) -> Vec<TransferRecord> {
+        let len = self.recent_transfers.len();
+        (len.saturating_sub(limit)..len).map(|index| self.internal_recent_transfer(index)).collect()
+    }
+
+    /// Returns the number of transfers kept in the ring of recent transfers.
+    pub fn get_max_recent_transfers(&self) -> u64 {
+        self.max_recent_transfers
+    }
+
+    /// Sets the number of transfers kept in the ring of recent transfers, dropping the oldest
+    /// ones if it shrinks. `0` disables the ring. Can only be called by the owner.
+    pub fn set_max_recent_transfers(&mut self, max_recent_transfers: u64) {
+        self.assert_owner();
+        assert!(
+            max_recent_transfers <= MAX_RECENT_TRANSFERS,
+            "The ring of recent transfers can't keep more than {} transfers",
+            MAX_RECENT_TRANSFERS
+        );
+        self.emit_admin_event(
+            "max_recent_transfers_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.max_recent_transfers,
+                new_value: max_recent_transfers,
+            },
+        );
+        // Stores the kept transfers from the oldest to the newest, so the ring can grow again.
+        let kept = self.recent_transfers(max_recent_transfers);
+        self.recent_transfers.clear();
+        self.recent_transfers.extend(kept);
+        self.recent_transfers_head = 0;
+        self.max_recent_transfers = max_recent_transfers;
+    }
+}
+
+impl Contract {
+    /// Adds a transfer of `amount` tokens to the ring of recent transfers, overwriting the oldest
+    /// one if the ring is full.
+    pub(crate) fn internal_record_recent_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+    ) {
+        if self.max_recent_transfers == 0 {
+            return;
+        }
+        let record = TransferRecord {
+            sender_id: sender_id.clone(),
+            receiver_id: receiver_id.clone(),
+            amount: amount.into(),
+            timestamp: env::block_timestamp(),
+        };
+        let len = self.recent_transfers.len();
+        if len < self.max_recent_transfers {
+            self.recent_transfers.push(&record);
+        } else {
+            self.recent_transfers.replace(self.recent_transfers_head, &record);
+            self.recent_transfers_head = (self.recent_transfers_head + 1) % len;
+        }
+    }
+
+    /// Returns the recent transfer at `index`, counted from the oldest one.
+    fn internal_recent_transfer(&self, index: u64) -> TransferRecord {
+        let len = self.recent_transfers.len();
+        self.recent_transfers.get((self.recent_transfers_head + index) % len).unwrap()
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        (context, contract)
+    }
+
+    fn record(sender: usize, receiver: usize, amount: Balance, timestamp: u64) -> TransferRecord {
+        TransferRecord {
+            sender_id: accounts(sender),
+            receiver_id: accounts(receiver),
+            amount: amount.into(),
+            timestamp,
+        }
+    }
+
+    /// Makes `count` transfers from charlie to bob, of 1, 2, ... tokens at the timestamps 1, 2, ...
+    fn transfer_many(context: &mut VMContextBuilder, contract: &mut Contract, count: u64) {
+        for i in 1..=count {
+            testing_env!(context.attached_deposit(1).block_timestamp(i).build());
+            contract.ft_transfer(accounts(1), (i as Balance).into(), None);
+        }
+    }
+
+    #[test]
+    fn test_recent_transfers() {
+        let (mut context, mut contract) = setup();
+        assert!(contract.recent_transfers(10).is_empty());
+
+        testing_env!(context.block_timestamp(1).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        testing_env!(context.block_timestamp(2).build());
+        contract.ft_transfer_batch(vec![(accounts(1), 10.into()), (accounts(3), 20.into())], None);
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(3).build());
+        contract.ft_transfer(accounts(3), 5.into(), None);
+
+        assert_eq!(
+            contract.recent_transfers(10),
+            vec![
+                record(2, 1, 100, 1),
+                record(2, 1, 10, 2),
+                record(2, 3, 20, 2),
+                record(1, 3, 5, 3)
+            ]
+        );
+        assert_eq!(contract.recent_transfers(2), vec![record(2, 3, 20, 2), record(1, 3, 5, 3)]);
+        assert!(contract.recent_transfers(0).is_empty());
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 25);
+    }
+
+    #[test]
+    fn test_recent_transfers_evicts_oldest() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_recent_transfers(3);
+        transfer_many(&mut context, &mut contract, 5);
+        assert_eq!(
+            contract.recent_transfers(10),
+            vec![record(2, 1, 3, 3), record(2, 1, 4, 4), record(2, 1, 5, 5)]
+        );
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 15);
+    }
+
+    #[test]
+    fn test_resize_recent_transfers() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_recent_transfers(3);
+        transfer_many(&mut context, &mut contract, 4);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_recent_transfers(2);
+        assert_eq!(contract.recent_transfers(10), vec![record(2, 1, 3, 3), record(2, 1, 4, 4)]);
+
+        contract.set_max_recent_transfers(4);
+        testing_env!(context.attached_deposit(1).block_timestamp(5).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+        assert_eq!(
+            contract.recent_transfers(10),
+            vec![record(2, 1, 3, 3), record(2, 1, 4, 4), record(2, 1, 5, 5)]
+        );
+    }
+
+    #[test]
+    fn test_disable_recent_transfers() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_recent_transfers(0);
+        transfer_many(&mut context, &mut contract, 2);
+        assert!(contract.recent_transfers(10).is_empty());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "The ring of recent transfers can't keep more than 1000 transfers")]
+    fn test_set_max_recent_transfers_too_large() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_recent_transfers(1_001);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_set_max_recent_transfers_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(1)).build());
+        contract.set_max_recent_transfers(10);
+    }
+}
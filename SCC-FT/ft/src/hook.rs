@@ -0,0 +1,212 @@
+//! An optional transfer hook, an external compliance contract that has to approve every transfer
+//! through its `can_transfer` method before it executes.
+//!
+//! Asking the hook is a cross-contract call, so a hooked transfer can't complete within the call
+//! that requested it. While a hook is set, `ft_transfer_call` only asks the hook and moves the
+//! tokens in the `on_transfer_hook_checked` callback, before notifying the receiver as usual. The
+//! balances are checked again when the callback runs, so the transfer can still fail there if the
+//! sender spent the tokens in the meantime. The synchronous transfers, e.g. `ft_transfer` and the
+//! batch transfers, can't wait for the hook and panic while it is set.
+use crate::events::SettingUpdate;
+use crate::*;
+use near_sdk::{serde_json, PromiseResult};
+
+/// The gas attached to the `can_transfer` call of the hook.
+const GAS_FOR_CAN_TRANSFER: Gas = Gas(10_000_000_000_000);
+
+#[ext_contract(ext_transfer_hook)]
+pub trait TransferHook {
+    fn can_transfer(&self, sender_id: AccountId, receiver_id: AccountId, amount: U128) -> bool;
+}
+
+#[ext_contract(ext_hook_resolver)]
+trait TransferHookResolver {
+    fn on_transfer_hook_checked(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128>;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the contract approving every transfer, if any.
+    pub fn get_transfer_hook(&self) -> Option<AccountId> {
+        self.transfer_hook.clone()
+    }
+
+    /// Sets the contract approving every transfer, or removes it with `None`. While a hook is
+    /// set, only `ft_transfer_call` can transfer tokens. Can only be called by the owner.
+    pub fn set_transfer_hook(&mut self, transfer_hook: Option<AccountId>) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "transfer_hook_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.transfer_hook.clone(),
+                new_value: transfer_hook.clone(),
+            },
+        );
+        self.transfer_hook = transfer_hook;
+    }
+
+    /// Executes the transfer of `ft_transfer_call` approved by the transfer hook and notifies the
+    /// receiver. Panics if the hook didn't approve the transfer, in which case no tokens move.
+    #[private]
+    pub fn on_transfer_hook_checked(
+        &mut self,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let allowed = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice(&value).unwrap_or(false),
+            _ => false,
+        };
+        assert!(allowed, "The transfer hook rejected the transfer");
+        let net_amount =
+            self.internal_execute_ft_transfer(&sender_id, &receiver_id, amount.0, memo);
+        self.internal_notify_receiver(sender_id, receiver_id, net_amount, msg).into()
+    }
+}
+
+impl Contract {
+    /// Panics if a transfer hook is set, for the transfers that can't wait for its approval.
+    pub(crate) fn assert_no_transfer_hook(&self) {
+        scc_assert!(
+            self.transfer_hook.is_none(),
+            SccError::TransferHookRequired,
+            "Transfers have to be approved by the transfer hook, use ft_transfer_call"
+        );
+    }
+
+    /// Returns the additional gas `ft_transfer_call` needs to ask the transfer hook, if any.
+    pub(crate) fn internal_transfer_hook_gas(&self) -> Gas {
+        if self.transfer_hook.is_some() {
+            GAS_FOR_CAN_TRANSFER + core_impl::GAS_FOR_FT_TRANSFER_CALL
+        } else {
+            Gas(0)
+        }
+    }
+
+    /// Asks `transfer_hook` whether the transfer is allowed and executes it in the
+    /// `on_transfer_hook_checked` callback.
+    pub(crate) fn internal_check_transfer_hook(
+        &self,
+        transfer_hook: AccountId,
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> Promise {
+        let callback_gas =
+            core_impl::GAS_FOR_FT_TRANSFER_CALL + self.on_transfer_gas + self.resolve_gas;
+        ext_transfer_hook::can_transfer(
+            sender_id.clone(),
+            receiver_id.clone(),
+            amount,
+            transfer_hook,
+            core_impl::NO_DEPOSIT,
+            GAS_FOR_CAN_TRANSFER,
+        )
+        .then(ext_hook_resolver::on_transfer_hook_checked(
+            sender_id,
+            receiver_id,
+            amount,
+            memo,
+            msg,
+            env::current_account_id(),
+            core_impl::NO_DEPOSIT,
+            callback_gas,
+        ))
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_transfer_hook(Some(accounts(4)));
+        (context, contract)
+    }
+
+    /// Runs the hook callback of a transfer of 100 tokens to `accounts(1)` where the mock hook
+    /// returned `allowed`.
+    fn resolve_hook(context: &mut VMContextBuilder, contract: &mut Contract, allowed: bool) {
+        testing_env!(
+            context
+                .predecessor_account_id(accounts(0))
+                .prepaid_gas(Gas(300_000_000_000_000))
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(serde_json::to_vec(&allowed).unwrap())]
+        );
+        contract.on_transfer_hook_checked(
+            accounts(2),
+            accounts(1),
+            100.into(),
+            None,
+            "".to_string(),
+        );
+    }
+
+    #[test]
+    fn test_transfer_call_waits_for_hook() {
+        let (mut context, mut contract) = setup();
+        assert_eq!(contract.get_transfer_hook(), Some(accounts(4)));
+        testing_env!(context.attached_deposit(1).prepaid_gas(Gas(300_000_000_000_000)).build());
+        contract.ft_transfer_call(accounts(1), 100.into(), None, "".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+
+        resolve_hook(&mut context, &mut contract, true);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer hook rejected the transfer")]
+    fn test_hook_denies_transfer() {
+        let (mut context, mut contract) = setup();
+        resolve_hook(&mut context, &mut contract, false);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers have to be approved by the transfer hook")]
+    fn test_transfer_with_hook() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    fn test_remove_hook() {
+        let (mut context, mut contract) = setup();
+        contract.set_transfer_hook(None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+/// How long an idempotency key is remembered, in nanoseconds (1 hour).
+const IDEMPOTENCY_WINDOW: u64 = 60 * 60 * 1_000_000_000;
+/// The number of recent idempotency keys remembered per sender.
+const MAX_IDEMPOTENCY_KEYS: usize = 16;
+/// The maximum length of an idempotency key in bytes.
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 64;
+/// The maximum bytes of the idempotency keys remembered for a sender, each serialized with its
+/// length and timestamp.
+pub(crate) const MAX_IDEMPOTENCY_KEYS_BYTES: u64 =
+    4 + MAX_IDEMPOTENCY_KEYS as u64 * (4 + MAX_IDEMPOTENCY_KEY_LEN as u64 + 8);
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens like `ft_transfer`, unless the caller already used `idempotency_key`
+    /// within the last hour, in which case nothing happens. Only the last 16 keys of every sender
+    /// are remembered. Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn ft_transfer_idempotent(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+        idempotency_key: Option<String>,
+    ) {
+        assert_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        if let Some(idempotency_key) = idempotency_key {
+            if !self.internal_use_idempotency_key(&sender_id, idempotency_key.clone()) {
+                log!("Duplicate idempotency key {}, the transfer was skipped", idempotency_key);
+                return;
+            }
+        }
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.into(), memo);
+    }
+}
+
+impl Contract {
+    /// Records the key for the sender, dropping the expired and the oldest keys. Returns `false`
+    /// if the key was already used within the window.
+    fn internal_use_idempotency_key(&mut self, sender_id: &AccountId, key: String) -> bool {
+        assert!(
+            !key.is_empty() && key.len() <= MAX_IDEMPOTENCY_KEY_LEN,
+            "The idempotency key should have between 1 and {} bytes",
+            MAX_IDEMPOTENCY_KEY_LEN
+        );
+        let now = env::block_timestamp();
+        let mut keys = self.idempotency_keys.get(sender_id).unwrap_or_default();
+        keys.retain(|(_, used_at)| now < used_at + IDEMPOTENCY_WINDOW);
+        if keys.iter().any(|(used_key, _)| used_key == &key) {
+            return false;
+        }
+        if keys.len() == MAX_IDEMPOTENCY_KEYS {
+            keys.remove(0);
+        }
+        keys.push((key, now));
+        self.idempotency_keys.insert(sender_id, &keys);
+        true
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(0)
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_duplicate_key_is_skipped() {
+        let (_, mut contract) = setup();
+        let key = Some("payout-1".to_string());
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, key.clone());
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, key);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, None);
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 30);
+    }
+
+    #[test]
+    fn test_key_expires() {
+        let (mut context, mut contract) = setup();
+        let key = Some("payout-1".to_string());
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, key.clone());
+
+        testing_env!(context.block_timestamp(IDEMPOTENCY_WINDOW).build());
+        contract.ft_transfer_idempotent(accounts(1), 10.into(), None, key);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 20);
+    }
+
+    #[test]
+    fn test_oldest_key_is_dropped() {
+        let (_, mut contract) = setup();
+        for index in 0..=MAX_IDEMPOTENCY_KEYS {
+            contract.ft_transfer_idempotent(accounts(1), 1.into(), None, Some(index.to_string()));
+        }
+        contract.ft_transfer_idempotent(accounts(1), 1.into(), None, Some("0".to_string()));
+        contract.ft_transfer_idempotent(accounts(1), 1.into(), None, Some("1".to_string()));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, MAX_IDEMPOTENCY_KEYS as u128 + 3);
+    }
+
+    #[test]
+    fn test_storage_bounds_cover_idempotency_keys() {
+        let (_, contract) = setup();
+        assert!(
+            contract.storage_balance_bounds().min.0
+                > Balance::from(MAX_IDEMPOTENCY_KEYS_BYTES) * env::storage_byte_cost()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::memo::parse_structured_memo;
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::serde_json::json;
+
+impl Contract {
+    /// Panics if a transfer from `sender_id` to `receiver_id` is not allowed by the current
+    /// contract restrictions.
+    pub(crate) fn assert_can_transfer(&self, sender_id: &AccountId, receiver_id: &AccountId) {
+        assert_not_contract(receiver_id);
+        self.assert_not_paused();
+        self.assert_not_frozen(sender_id);
+        self.assert_not_frozen(receiver_id);
+        self.assert_whitelisted(sender_id, receiver_id);
+    }
+
+    /// Moves `amount` tokens from `sender_id` to `receiver_id` after checking the transfer
+    /// restrictions. The caller is responsible for emitting the transfer event.
+    pub(crate) fn internal_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+    ) {
+        scc_assert!(
+            sender_id != receiver_id,
+            SccError::SelfTransfer,
+            "Sender and receiver should be different"
+        );
+        self.assert_nonzero_transfer(amount);
+        self.assert_can_transfer(sender_id, receiver_id);
+        self.internal_settle_reflection(sender_id);
+        self.internal_settle_reflection(receiver_id);
+        self.internal_withdraw(sender_id, amount);
+        self.internal_deposit(receiver_id, amount);
+        self.internal_record_activity(sender_id, receiver_id);
+    }
+
+    /// Moves `amount` tokens from `sender_id`, charging the transfer fee to the fee collector,
+    /// burning the transfer burn, skimming the reflection fee and crediting the rest to
+    /// `receiver_id`. Returns the amount credited to the receiver and the charged fee. The burn
+    /// and reflection events are emitted here, the caller is responsible for emitting the
+    /// transfer events.
+    pub(crate) fn internal_transfer_with_fee(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+    ) -> (Balance, Balance) {
+        self.assert_min_transfer_amount(amount);
+        self.assert_allowed_receiver(sender_id, receiver_id);
+        self.internal_record_daily_spend(sender_id, amount);
+        self.internal_use_large_transfer_approval(sender_id, amount);
+        let fee = self.internal_transfer_fee(sender_id, receiver_id, amount);
+        let preview = self.internal_preview_transfer(amount, fee);
+        let fee = preview.fee.0;
+        let fee_collector = self.fee_collector.clone();
+        if fee > 0 {
+            self.internal_transfer(sender_id, &fee_collector, fee);
+        }
+        if preview.burn.0 > 0 {
+            self.internal_burn(sender_id, preview.burn, Some("Transfer burn".to_string()));
+        }
+        if preview.reflection_fee.0 > 0 {
+            self.internal_reflect(sender_id, preview.reflection_fee.0);
+        }
+        self.internal_transfer(sender_id, receiver_id, preview.net_amount.0);
+        self.internal_record_transfer_stats(amount);
+        self.internal_record_recent_transfer(sender_id, receiver_id, amount);
+        (preview.net_amount.0, fee)
+    }
+
+    /// Transfers `amount` tokens from `sender_id` to `receiver_id` the same way `ft_transfer`
+    /// does, emitting the transfer events. Panics while a transfer hook is set, since such
+    /// transfers have to wait for the hook. Returns the amount credited to the receiver.
+    pub(crate) fn internal_ft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        self.assert_no_transfer_hook();
+        self.internal_execute_ft_transfer(sender_id, receiver_id, amount, memo)
+    }
+
+    /// Transfers `amount` tokens from `sender_id` to `receiver_id`, emitting the transfer events.
+    /// Panics if a memo is required and missing. A structured memo is additionally emitted parsed
+    /// in an `ft_transfer` event of the contract standard. Returns the amount credited to the
+    /// receiver.
+    pub(crate) fn internal_execute_ft_transfer(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Balance {
+        self.assert_memo_present(memo.as_deref());
+        self.assert_valid_memo(memo.as_deref());
+        self.internal_apply_transfer_cooldown(sender_id);
+        let (net_amount, fee) = self.internal_transfer_with_fee(sender_id, receiver_id, amount);
+        let net_amount_json = U128(net_amount);
+        let fee_json = U128(fee);
+        let mut events = vec![FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            amount: &net_amount_json,
+            memo: memo.as_deref(),
+        }];
+        if fee > 0 {
+            events.push(FtTransfer {
+                old_owner_id: sender_id,
+                new_owner_id: &self.fee_collector,
+                amount: &fee_json,
+                memo: Some("Transfer fee"),
+            });
+        }
+        self.emit_transfers(&events);
+        if let Some(structured_memo) = memo.as_deref().and_then(parse_structured_memo) {
+            self.emit_event(
+                "ft_transfer",
+                &[json!({
+                    "old_owner_id": sender_id,
+                    "new_owner_id": receiver_id,
+                    "amount": net_amount_json,
+                    "memo": structured_memo,
+                })],
+            );
+        }
+        net_amount
+    }
+
+    /// Deposits `amount` to the balance of `account_id`, counting it as a new holder and
+    /// notifying the new holder contract if its balance was zero.
+    pub(crate) fn internal_deposit(&mut self, account_id: &AccountId, amount: Balance) {
+        let old_balance = self.internal_registered_balance(account_id);
+        self.token.internal_deposit(account_id, amount);
+        self.internal_update_holders(old_balance, old_balance + amount);
+        if old_balance == 0 && amount > 0 {
+            self.internal_notify_new_holder(account_id);
+        }
+    }
+
+    /// Withdraws `amount` from the balance of `account_id`, no longer counting it as a holder if
+    /// its balance drops to zero.
+    pub(crate) fn internal_withdraw(&mut self, account_id: &AccountId, amount: Balance) {
+        let old_balance = self.internal_registered_balance(account_id);
+        scc_assert!(
+            old_balance >= amount,
+            SccError::InsufficientBalance,
+            "The account doesn't have enough balance"
+        );
+        self.token.internal_withdraw(account_id, amount);
+        self.internal_update_holders(old_balance, old_balance - amount);
+    }
+
+    /// Returns the stored balance of `account_id`, panicking with the same message as the token
+    /// if the account is not registered.
+    fn internal_registered_balance(&self, account_id: &AccountId) -> Balance {
+        self.token.accounts.get(account_id).unwrap_or_else(|| {
+            SccError::NotRegistered.panic(&format!("The account {} is not registered", account_id))
+        })
+    }
+
+    /// Updates the number of holders after a balance changed from `old_balance` to
+    /// `new_balance`.
+    pub(crate) fn internal_update_holders(&mut self, old_balance: Balance, new_balance: Balance) {
+        if old_balance == 0 && new_balance > 0 {
+            self.holders += 1;
+        } else if old_balance > 0 && new_balance == 0 {
+            // Holders from before the migration of the initial release were never counted.
+            self.holders = self.holders.saturating_sub(1);
+        }
+    }
+}
+
+/// Panics if `receiver_id` is the token contract itself, since the tokens would be stuck there.
+pub(crate) fn assert_not_contract(receiver_id: &AccountId) {
+    scc_assert!(
+        receiver_id != &env::current_account_id(),
+        SccError::ContractReceiver,
+        "Cannot transfer to the token contract"
+    );
+}
@@ -0,0 +1,117 @@
+use crate::events::SettingUpdate;
+use crate::*;
+
+/// The maximum length of a label in bytes.
+const MAX_LABEL_LEN: usize = 64;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the label of the given account, e.g. "Treasury", if any.
+    pub fn get_label(&self, account_id: AccountId) -> Option<String> {
+        self.labels.get(&account_id)
+    }
+
+    /// Labels the given account for block explorers, replacing its previous label. Can only be
+    /// called by the owner.
+    pub fn set_label(&mut self, account_id: AccountId, label: String) {
+        self.assert_owner();
+        assert!(
+            !label.is_empty() && label.len() <= MAX_LABEL_LEN,
+            "The label should be between 1 and {} bytes long",
+            MAX_LABEL_LEN
+        );
+        let old_label = self.labels.insert(&account_id, &label);
+        self.emit_admin_event(
+            "label_update",
+            SettingUpdate {
+                account_id: Some(account_id),
+                old_value: old_label,
+                new_value: Some(label),
+            },
+        );
+    }
+
+    /// Removes the label of the given account. Can only be called by the owner.
+    pub fn remove_label(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        let old_label = self
+            .labels
+            .remove(&account_id)
+            .unwrap_or_else(|| env::panic_str(&format!("The account {} has no label", account_id)));
+        self.emit_admin_event(
+            "label_update",
+            SettingUpdate {
+                account_id: Some(account_id),
+                old_value: Some(old_label),
+                new_value: None,
+            },
+        );
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(2))
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_set_and_remove_label() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.get_label(accounts(1)), None);
+        contract.set_label(accounts(1), "Liquidity".to_string());
+        assert_eq!(contract.get_label(accounts(1)), Some("Liquidity".to_string()));
+        contract.set_label(accounts(1), "Team".to_string());
+        assert_eq!(contract.get_label(accounts(1)), Some("Team".to_string()));
+        contract.remove_label(accounts(1));
+        assert_eq!(contract.get_label(accounts(1)), None);
+    }
+
+    #[test]
+    fn test_labels_in_accounts() {
+        let (_, mut contract) = setup();
+        contract.set_label(accounts(2), "Treasury".to_string());
+        assert_eq!(
+            contract.get_accounts(None, None),
+            vec![
+                AccountEntry {
+                    account_id: accounts(2),
+                    balance: U128(TOTAL_SUPPLY),
+                    label: Some("Treasury".to_string())
+                },
+                AccountEntry { account_id: accounts(1), balance: U128(0), label: None },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The label should be between 1 and 64 bytes long")]
+    fn test_label_too_long() {
+        let (_, mut contract) = setup();
+        contract.set_label(accounts(1), "a".repeat(65));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_set_label_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_label(accounts(1), "Team".to_string());
+    }
+}
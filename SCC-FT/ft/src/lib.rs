@@ -15,18 +15,201 @@ NOTES:
   - To prevent the deployed contract from being modified or deleted, it should not have any access
     keys on its account.
 */
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
 use near_contract_standards::fungible_token::FungibleToken;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::LazyOption;
-use near_sdk::json_types::U128;
-use near_sdk::{env, log, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue};
+use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet, Vector};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::{
+    env, ext_contract, log, near_bindgen, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault,
+    Promise, PromiseOrValue,
+};
+
+// Declared first, so that its macros can be used by the other modules.
+#[macro_use]
+mod errors;
+mod activity;
+mod airdrop;
+mod allowance;
+mod batch;
+mod buyback;
+#[cfg(feature = "clawback")]
+mod clawback;
+mod config;
+mod core_impl;
+mod denylist;
+mod emergency;
+mod emission;
+mod enumeration;
+mod events;
+mod fee;
+mod freeze;
+mod history;
+mod hook;
+mod idempotency;
+mod internal;
+mod label;
+mod limits;
+mod memo;
+mod metadata;
+mod migrate;
+mod minters;
+mod multisig;
+mod new_holder;
+mod owner;
+mod pause;
+mod permit;
+mod recovery;
+mod redenomination;
+mod reflection;
+mod snapshot;
+mod split;
+mod storage_impl;
+mod supply;
+mod timelock;
+mod vesting;
+mod voucher;
+mod whitelist;
+mod wrap;
+
+pub use crate::config::Config;
+pub use crate::core_impl::TransferCallGas;
+pub use crate::emission::EmissionEntry;
+pub use crate::enumeration::AccountEntry;
+pub use crate::errors::SccError;
+pub use crate::fee::{RoundingMode, TransferPreview};
+pub use crate::history::TransferRecord;
+pub use crate::multisig::Proposal;
+pub use crate::timelock::{QueuedAction, TimelockAction};
+pub use crate::vesting::VestingSchedule;
+pub use crate::voucher::Voucher;
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    FrozenAccounts,
+    Minters,
+    RegisteredAccounts,
+    Vesting,
+    Allowances,
+    ExcludedAccounts,
+    SnapshotBalances,
+    PermitKeys,
+    PermitNonces,
+    Blacklist,
+    TimelockActions,
+    TransferWhitelist,
+    EmissionSchedule,
+    ReflectionCheckpoints,
+    StorageDeposits,
+    Proposals,
+    IdempotencyKeys,
+    LastActivity,
+    LastTransferSent,
+    LargeTransferApprovals,
+    FeeExempt,
+    Vouchers,
+    AllowedReceivers,
+    VestingGrants { account_hash: Vec<u8> },
+    EmergencyRecipients,
+    Labels,
+    DailyLimits,
+    SpentToday,
+    RecentTransfers,
+    RegistrationDeposits,
+    AllowanceSpenders,
+}
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
+    owner_id: AccountId,
+    pending_owner: Option<AccountId>,
+    paused: bool,
+    frozen_accounts: UnorderedSet<AccountId>,
+    minters: UnorderedSet<AccountId>,
+    max_supply: Option<Balance>,
+    transfer_fee_bps: u16,
+    fee_collector: AccountId,
+    registered_accounts: UnorderedSet<AccountId>,
+    vesting: LookupMap<AccountId, Vector<VestingSchedule>>,
+    vesting_escrow_balance: Balance,
+    allowances: LookupMap<(AccountId, AccountId), (Balance, Option<u64>)>,
+    excluded_accounts: UnorderedSet<AccountId>,
+    mint_limit_per_day: Option<Balance>,
+    minted_today: Balance,
+    current_day: u64,
+    snapshot_balances: LookupMap<(u64, AccountId), Balance>,
+    snapshot_count: u64,
+    permit_keys: LookupMap<AccountId, Vec<u8>>,
+    permit_nonces: LookupMap<AccountId, u64>,
+    on_transfer_gas: Gas,
+    resolve_gas: Gas,
+    blacklist: LookupMap<AccountId, String>,
+    timelock_delay: u64,
+    timelock_actions: LookupMap<String, QueuedAction>,
+    total_burned: Balance,
+    transfer_whitelist_enabled: bool,
+    transfer_whitelist: UnorderedSet<AccountId>,
+    require_memo: bool,
+    emission_schedule: Vector<EmissionEntry>,
+    burn_rate_bps: u16,
+    reflection_fee_bps: u16,
+    reflection_pool: Balance,
+    reflection_index: u128,
+    reflection_checkpoints: LookupMap<AccountId, u128>,
+    min_transfer_amount: Balance,
+    storage_deposits: LookupMap<AccountId, Balance>,
+    multisig_owners: Vec<AccountId>,
+    multisig_threshold: u8,
+    proposals: LookupMap<u64, Proposal>,
+    proposal_count: u64,
+    idempotency_keys: LookupMap<AccountId, Vec<(String, u64)>>,
+    last_activity: LookupMap<AccountId, u64>,
+    transfer_cooldown_seconds: u64,
+    last_transfer_sent: LookupMap<AccountId, u64>,
+    event_version: String,
+    holders: u64,
+    wrapped: bool,
+    large_transfer_threshold: Balance,
+    large_transfer_approvals: LookupMap<AccountId, Balance>,
+    deployer: AccountId,
+    deployed_at: u64,
+    fee_exempt: UnorderedSet<AccountId>,
+    pending_snapshot: Option<(u64, u64)>,
+    allow_self_transfer: bool,
+    emit_balances: bool,
+    vouchers: LookupMap<Vec<u8>, Voucher>,
+    allowed_receivers: LookupMap<AccountId, Vec<AccountId>>,
+    total_transfer_volume: Balance,
+    total_transfer_count: u64,
+    supply_warning_bps: Option<u16>,
+    supply_warning_emitted: bool,
+    transfer_hook: Option<AccountId>,
+    pending_redenomination: Option<(u8, Balance, u64)>,
+    denylist_sync: Option<(AccountId, u64)>,
+    allow_zero_transfers: bool,
+    ownership_renounced: bool,
+    max_memo_bytes: u32,
+    rounding_mode: RoundingMode,
+    emergency_recipients: LookupMap<AccountId, AccountId>,
+    refund_on_receiver_panic: bool,
+    labels: LookupMap<AccountId, String>,
+    daily_limits: LookupMap<AccountId, Balance>,
+    spent_today: LookupMap<AccountId, (u64, Balance)>,
+    on_new_holder_contract: Option<AccountId>,
+    recent_transfers: Vector<TransferRecord>,
+    recent_transfers_head: u64,
+    max_recent_transfers: u64,
+    pending_split: Option<(Balance, Balance, u64, Balance)>,
+    registration_deposits: LookupMap<AccountId, Balance>,
+    allowance_spenders: LookupMap<AccountId, Vec<AccountId>>,
     token: FungibleToken,
     metadata: LazyOption<FungibleTokenMetadata>,
 }
@@ -36,7 +219,7 @@ const DATA_IMAGE_SCC_ICON: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgA
 #[near_bindgen]
 impl Contract {
     /// Initializes the contract with the given total supply owned by the given `owner_id` with
-    /// default metadata (for example purposes only).
+    /// default metadata (for example purposes only). Can only be called by the contract account.
     #[init]
     pub fn new_default_meta(owner_id: AccountId, total_supply: U128) -> Self {
         Self::new(
@@ -51,25 +234,91 @@ impl Contract {
                 reference_hash: None,
                 decimals: 8,
             },
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    /// Initializes the contract with the given total supply owned by the given `owner_id` with
+    /// metadata made of the given `name`, `symbol` and `decimals`, without an icon or a
+    /// reference, and the defaults of `new` for the other settings. Can only be called by the
+    /// contract account.
+    #[init]
+    pub fn new_with(
+        owner_id: AccountId,
+        total_supply: U128,
+        name: String,
+        symbol: String,
+        decimals: u8,
+    ) -> Self {
+        metadata::assert_valid_symbol(&symbol);
+        Self::new(
+            owner_id,
+            total_supply,
+            FungibleTokenMetadata {
+                spec: FT_METADATA_SPEC.to_string(),
+                name,
+                symbol,
+                icon: None,
+                reference: None,
+                reference_hash: None,
+                decimals,
+            },
+            None,
+            None,
+            None,
+            None,
         )
     }
 
     /// Initializes the contract with the given total supply owned by the given `owner_id` with
-    /// the given fungible token metadata.
+    /// the given fungible token metadata. If `max_supply` is given, minting can never push the
+    /// total supply above it. `timelock_delay` is the immutable delay in nanoseconds for the
+    /// timelocked owner actions, 1 day by default. `event_version` is the version of the events
+    /// of the contract standards, "1.0.0" by default. A `wrapped` token is backed 1:1 by NEAR
+    /// and starts with a zero total supply. Can only be called by the contract account.
     #[init]
     pub fn new(
         owner_id: AccountId,
         total_supply: U128,
         metadata: FungibleTokenMetadata,
+        max_supply: Option<U128>,
+        timelock_delay: Option<U64>,
+        event_version: Option<String>,
+        wrapped: Option<bool>,
     ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
+        // Nobody else can front-run the initialization between the deployment and the init call.
+        assert_eq!(
+            env::predecessor_account_id(),
+            env::current_account_id(),
+            "The contract can only be initialized by the contract account"
+        );
         metadata.assert_valid();
-        let mut this = Self {
-            token: FungibleToken::new(b"a".to_vec()),
-            metadata: LazyOption::new(b"m".to_vec(), Some(&metadata)),
-        };
-        this.token.internal_register_account(&owner_id);
-        this.token.internal_deposit(&owner_id, total_supply.into());
+        metadata::assert_valid_decimals(metadata.decimals);
+        if let Some(max_supply) = max_supply {
+            assert!(total_supply.0 <= max_supply.0, "Total supply exceeds the max supply");
+        }
+        let mut this = Self::internal_new(
+            owner_id.clone(),
+            FungibleToken::new(b"a".to_vec()),
+            LazyOption::new(b"m".to_vec(), Some(&metadata)),
+            max_supply.map(|max_supply| max_supply.0),
+            timelock_delay.map_or(timelock::DEFAULT_TIMELOCK_DELAY, |delay| delay.0),
+        );
+        let wrapped = wrapped.unwrap_or(false);
+        if wrapped {
+            assert_eq!(total_supply.0, 0, "A wrapped token should start with a zero total supply");
+        }
+        if let Some(event_version) = event_version {
+            events::assert_valid_event_version(&event_version);
+            this.event_version = event_version;
+        }
+        this.wrapped = wrapped;
+        this.internal_register_account(&owner_id);
+        this.internal_deposit(&owner_id, total_supply.into());
         near_contract_standards::fungible_token::events::FtMint {
             owner_id: &owner_id,
             amount: &total_supply,
@@ -79,18 +328,238 @@ impl Contract {
         this
     }
 
-    fn on_account_closed(&mut self, account_id: AccountId, balance: Balance) {
+    /// Builds the contract state around the given token and metadata with every other setting
+    /// at its default value.
+    fn internal_new(
+        owner_id: AccountId,
+        token: FungibleToken,
+        metadata: LazyOption<FungibleTokenMetadata>,
+        max_supply: Option<Balance>,
+        timelock_delay: u64,
+    ) -> Self {
+        Self {
+            owner_id: owner_id.clone(),
+            pending_owner: None,
+            paused: false,
+            frozen_accounts: UnorderedSet::new(StorageKey::FrozenAccounts),
+            minters: UnorderedSet::new(StorageKey::Minters),
+            max_supply,
+            transfer_fee_bps: 0,
+            fee_collector: owner_id,
+            registered_accounts: UnorderedSet::new(StorageKey::RegisteredAccounts),
+            vesting: LookupMap::new(StorageKey::Vesting),
+            vesting_escrow_balance: 0,
+            allowances: LookupMap::new(StorageKey::Allowances),
+            excluded_accounts: UnorderedSet::new(StorageKey::ExcludedAccounts),
+            mint_limit_per_day: None,
+            minted_today: 0,
+            current_day: 0,
+            snapshot_balances: LookupMap::new(StorageKey::SnapshotBalances),
+            snapshot_count: 0,
+            permit_keys: LookupMap::new(StorageKey::PermitKeys),
+            permit_nonces: LookupMap::new(StorageKey::PermitNonces),
+            on_transfer_gas: core_impl::DEFAULT_GAS_FOR_ON_TRANSFER,
+            resolve_gas: core_impl::DEFAULT_GAS_FOR_RESOLVE_TRANSFER,
+            blacklist: LookupMap::new(StorageKey::Blacklist),
+            timelock_delay,
+            timelock_actions: LookupMap::new(StorageKey::TimelockActions),
+            total_burned: 0,
+            transfer_whitelist_enabled: false,
+            transfer_whitelist: UnorderedSet::new(StorageKey::TransferWhitelist),
+            require_memo: false,
+            emission_schedule: Vector::new(StorageKey::EmissionSchedule),
+            burn_rate_bps: 0,
+            reflection_fee_bps: 0,
+            reflection_pool: 0,
+            reflection_index: 0,
+            reflection_checkpoints: LookupMap::new(StorageKey::ReflectionCheckpoints),
+            min_transfer_amount: 0,
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            multisig_owners: Vec::new(),
+            multisig_threshold: 1,
+            proposals: LookupMap::new(StorageKey::Proposals),
+            proposal_count: 0,
+            idempotency_keys: LookupMap::new(StorageKey::IdempotencyKeys),
+            last_activity: LookupMap::new(StorageKey::LastActivity),
+            transfer_cooldown_seconds: 0,
+            last_transfer_sent: LookupMap::new(StorageKey::LastTransferSent),
+            event_version: events::DEFAULT_EVENT_VERSION.to_string(),
+            holders: 0,
+            wrapped: false,
+            large_transfer_threshold: 0,
+            large_transfer_approvals: LookupMap::new(StorageKey::LargeTransferApprovals),
+            deployer: env::predecessor_account_id(),
+            deployed_at: env::block_timestamp(),
+            fee_exempt: UnorderedSet::new(StorageKey::FeeExempt),
+            pending_snapshot: None,
+            allow_self_transfer: false,
+            emit_balances: false,
+            vouchers: LookupMap::new(StorageKey::Vouchers),
+            allowed_receivers: LookupMap::new(StorageKey::AllowedReceivers),
+            total_transfer_volume: 0,
+            total_transfer_count: 0,
+            supply_warning_bps: None,
+            supply_warning_emitted: false,
+            transfer_hook: None,
+            pending_redenomination: None,
+            denylist_sync: None,
+            allow_zero_transfers: false,
+            ownership_renounced: false,
+            max_memo_bytes: memo::DEFAULT_MAX_MEMO_BYTES,
+            rounding_mode: RoundingMode::Floor,
+            emergency_recipients: LookupMap::new(StorageKey::EmergencyRecipients),
+            refund_on_receiver_panic: true,
+            labels: LookupMap::new(StorageKey::Labels),
+            daily_limits: LookupMap::new(StorageKey::DailyLimits),
+            spent_today: LookupMap::new(StorageKey::SpentToday),
+            on_new_holder_contract: None,
+            recent_transfers: Vector::new(StorageKey::RecentTransfers),
+            recent_transfers_head: 0,
+            max_recent_transfers: history::DEFAULT_MAX_RECENT_TRANSFERS,
+            pending_split: None,
+            registration_deposits: LookupMap::new(StorageKey::RegistrationDeposits),
+            allowance_spenders: LookupMap::new(StorageKey::AllowanceSpenders),
+            token,
+            metadata,
+        }
+    }
+
+    /// Mints `amount` new tokens to the registered `account_id`. Can only be called by the owner
+    /// or a minter, and not for wrapped NEAR.
+    pub fn mint(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+        self.assert_minter();
+        self.assert_not_wrapped();
+        self.internal_record_daily_mint(amount.0);
+        self.internal_mint(&account_id, amount.0, memo.as_deref());
+    }
+
+    /// Returns the cap on the total supply, if any.
+    pub fn get_max_supply(&self) -> Option<U128> {
+        self.max_supply.map(U128)
+    }
+
+    /// Burns `amount` tokens from the caller's own balance.
+    pub fn burn(&mut self, amount: U128, memo: Option<String>) {
+        let account_id = env::predecessor_account_id();
+        self.internal_burn(&account_id, amount, memo);
+    }
+
+    /// Returns the total amount of tokens burned since the deployment.
+    pub fn ft_total_burned(&self) -> U128 {
+        self.total_burned.into()
+    }
+
+    /// Returns the account that initialized the contract and the timestamp of the initialization
+    /// in nanoseconds. For a migrated contract these are the account and the time of the
+    /// migration.
+    pub fn get_deployment_info(&self) -> (AccountId, U64) {
+        (self.deployer.clone(), self.deployed_at.into())
+    }
+
+    /// Burns `amount` tokens from the given `account_id`. Can only be called by the owner.
+    pub fn burn_from(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+        self.assert_owner();
+        self.internal_burn(&account_id, amount, memo);
+    }
+
+    /// Sets the max supply, which can only be lowered, and never below the current total supply.
+    /// A token without a max supply can be capped, but a cap can't be raised or removed.
+    pub(crate) fn internal_set_max_supply(&mut self, max_supply: Option<Balance>) {
+        let max_supply_lowered = match (self.max_supply, max_supply) {
+            (Some(current), Some(max_supply)) => max_supply <= current,
+            (None, _) => true,
+            (Some(_), None) => false,
+        };
+        assert!(max_supply_lowered, "The max supply can only be lowered");
+        if let Some(max_supply) = max_supply {
+            assert!(
+                self.token.total_supply <= max_supply,
+                "The max supply can't be below the total supply"
+            );
+        }
+        self.emit_admin_event(
+            "max_supply_update",
+            events::SettingUpdate {
+                account_id: None,
+                old_value: self.max_supply.map(U128),
+                new_value: max_supply.map(U128),
+            },
+        );
+        self.max_supply = max_supply;
+    }
+
+    /// Mints `amount` new tokens to the registered `account_id`, respecting the max supply.
+    pub(crate) fn internal_mint(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<&str>,
+    ) {
+        self.assert_valid_memo(memo);
+        internal::assert_not_contract(account_id);
+        scc_assert!(
+            self.token.accounts.contains_key(account_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            account_id
+        );
+        if let Some(max_supply) = self.max_supply {
+            let new_total_supply = self.token.total_supply.checked_add(amount);
+            scc_assert!(
+                new_total_supply.map_or(false, |total_supply| total_supply <= max_supply),
+                SccError::MaxSupplyExceeded,
+                "Minting would exceed the max supply"
+            );
+        }
+        self.internal_settle_reflection(account_id);
+        self.internal_deposit(account_id, amount);
+        near_contract_standards::fungible_token::events::FtMint {
+            owner_id: account_id,
+            amount: &U128(amount),
+            memo,
+        }
+        .emit();
+        self.internal_update_supply_warning();
+    }
+
+    pub(crate) fn internal_burn(
+        &mut self,
+        account_id: &AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        self.assert_valid_memo(memo.as_deref());
+        self.internal_settle_reflection(account_id);
+        self.internal_withdraw(account_id, amount.into());
+        self.total_burned += amount.0;
+        near_contract_standards::fungible_token::events::FtBurn {
+            owner_id: account_id,
+            amount: &amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+        self.internal_update_supply_warning();
+    }
+
+    /// Logs the closed account together with the released storage deposit refunded to
+    /// `refund_id`, so the refund can be reconciled with the registration deposit.
+    pub(crate) fn on_account_closed(
+        &mut self,
+        account_id: AccountId,
+        balance: Balance,
+        refund_id: &AccountId,
+        refund: Balance,
+    ) {
         log!("Closed @{} with {}", account_id, balance);
+        log!("Refunded {} yoctoNEAR of released storage to @{}", refund, refund_id);
     }
 
-    fn on_tokens_burned(&mut self, account_id: AccountId, amount: Balance) {
+    pub(crate) fn on_tokens_burned(&mut self, account_id: AccountId, amount: Balance) {
+        self.total_burned += amount;
         log!("Account @{} burned {}", account_id, amount);
     }
 }
 
-near_contract_standards::impl_fungible_token_core!(Contract, token, on_tokens_burned);
-near_contract_standards::impl_fungible_token_storage!(Contract, token, on_account_closed);
-
 #[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
     fn ft_metadata(&self) -> FungibleTokenMetadata {
@@ -106,9 +575,11 @@ mod tests {
 
     use super::*;
 
-    const TOTAL_SUPPLY: Balance = 1_000_000_000_000_000;
+    pub(crate) const TOTAL_SUPPLY: Balance = 1_000_000_000_000_000;
+    /// A deposit of 0.01 NEAR, enough to cover the storage added by a single call.
+    pub(crate) const STORAGE_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
 
-    fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
+    pub(crate) fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
         builder
             .current_account_id(accounts(0))
@@ -117,16 +588,133 @@ mod tests {
         builder
     }
 
+    /// Runs the contract initialization `init` from the contract account, the only account
+    /// allowed to initialize it, and restores `context` afterwards.
+    pub(crate) fn init_contract(
+        context: &VMContextBuilder,
+        init: impl FnOnce() -> Contract,
+    ) -> Contract {
+        testing_env!(context.clone().predecessor_account_id(accounts(0)).build());
+        let contract = init();
+        testing_env!(context.clone().build());
+        contract
+    }
+
+    pub(crate) fn test_metadata() -> FungibleTokenMetadata {
+        FungibleTokenMetadata {
+            spec: FT_METADATA_SPEC.to_string(),
+            name: "Test Coin".to_string(),
+            symbol: "TEST".to_string(),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 8,
+        }
+    }
+
+    pub(crate) fn register_account(
+        context: &mut VMContextBuilder,
+        contract: &mut Contract,
+        account_id: AccountId,
+    ) {
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(contract.storage_balance_bounds().min.into())
+            .predecessor_account_id(account_id)
+            .build());
+        contract.storage_deposit(None, None);
+    }
+
     #[test]
     fn test_new() {
         let mut context = get_context(accounts(1));
         testing_env!(context.build());
-        let contract = Contract::new_default_meta(accounts(1).into(), TOTAL_SUPPLY.into());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1).into(), TOTAL_SUPPLY.into())
+        });
         testing_env!(context.is_view(true).build());
         assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
         assert_eq!(contract.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY);
     }
 
+    #[test]
+    fn test_new_with() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_with(
+                accounts(1),
+                TOTAL_SUPPLY.into(),
+                "Simple Token".to_string(),
+                "SIMPLE".to_string(),
+                18,
+            )
+        });
+        let metadata = contract.ft_metadata();
+        assert_eq!(metadata.spec, FT_METADATA_SPEC);
+        assert_eq!(metadata.name, "Simple Token");
+        assert_eq!(metadata.symbol, "SIMPLE");
+        assert_eq!(metadata.decimals, 18);
+        assert!(metadata.icon.is_none());
+        assert!(metadata.reference.is_none() && metadata.reference_hash.is_none());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    #[should_panic(expected = "The symbol should be between 1 and 16 bytes long")]
+    fn test_new_with_long_symbol() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        Contract::new_with(
+            accounts(1),
+            TOTAL_SUPPLY.into(),
+            "Simple Token".to_string(),
+            "S".repeat(17),
+            18,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The decimals can't exceed 24")]
+    fn test_new_with_too_many_decimals() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        Contract::new_with(
+            accounts(1),
+            TOTAL_SUPPLY.into(),
+            "Simple Token".to_string(),
+            "SIMPLE".to_string(),
+            25,
+        );
+    }
+
+    #[test]
+    fn test_get_owner() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.is_view(true).build());
+        assert_eq!(contract.get_owner(), accounts(2));
+    }
+
+    #[test]
+    fn test_init_from_contract_account() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let contract = Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into());
+        assert_eq!(contract.get_owner(), accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract can only be initialized by the contract account")]
+    fn test_init_from_other_account() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into());
+    }
+
     #[test]
     #[should_panic(expected = "The contract is not initialized")]
     fn test_default() {
@@ -139,7 +727,9 @@ mod tests {
     fn test_transfer() {
         let mut context = get_context(accounts(2));
         testing_env!(context.build());
-        let mut contract = Contract::new_default_meta(accounts(2).into(), TOTAL_SUPPLY.into());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2).into(), TOTAL_SUPPLY.into())
+        });
         testing_env!(context
             .storage_usage(env::storage_usage())
             .attached_deposit(contract.storage_balance_bounds().min.into())
@@ -165,4 +755,228 @@ mod tests {
         assert_eq!(contract.ft_balance_of(accounts(2)).0, (TOTAL_SUPPLY - transfer_amount));
         assert_eq!(contract.ft_balance_of(accounts(1)).0, transfer_amount);
     }
+
+    #[test]
+    #[should_panic(expected = "Cannot transfer to the token contract")]
+    fn test_transfer_to_contract() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(0));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(0), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot transfer to the token contract")]
+    fn test_mint_to_contract() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.mint(accounts(0), 10.into(), None);
+    }
+
+    #[test]
+    fn test_mint() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        let mint_amount = TOTAL_SUPPLY / 4;
+        contract.mint(accounts(1), mint_amount.into(), Some("Reward".to_string()));
+
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + mint_amount);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, mint_amount);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or a minter can call this method")]
+    fn test_mint_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.mint(accounts(2), 1.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "is not registered")]
+    fn test_mint_unregistered() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.mint(accounts(3), 1.into(), None);
+    }
+
+    #[test]
+    fn test_deployment_info() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.block_timestamp(1_000).build());
+        let contract = Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into());
+        assert_eq!(contract.get_deployment_info(), (accounts(0), U64(1_000)));
+    }
+
+    #[test]
+    fn test_mint_up_to_max_supply() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let max_supply = TOTAL_SUPPLY + 100;
+        let mut contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                TOTAL_SUPPLY.into(),
+                test_metadata(),
+                Some(max_supply.into()),
+                None,
+                None,
+                None,
+            )
+        });
+        assert_eq!(contract.get_max_supply(), Some(max_supply.into()));
+
+        contract.mint(accounts(2), 100.into(), None);
+        assert_eq!(contract.ft_total_supply().0, max_supply);
+    }
+
+    #[test]
+    #[should_panic(expected = "Minting would exceed the max supply")]
+    fn test_mint_above_max_supply() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                TOTAL_SUPPLY.into(),
+                test_metadata(),
+                Some((TOTAL_SUPPLY + 100).into()),
+                None,
+                None,
+                None,
+            )
+        });
+        contract.mint(accounts(2), 100.into(), None);
+        contract.mint(accounts(2), 1.into(), None);
+    }
+
+    #[test]
+    fn test_mint_without_max_supply() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(accounts(2), TOTAL_SUPPLY.into(), test_metadata(), None, None, None, None)
+        });
+        assert_eq!(contract.get_max_supply(), None);
+
+        contract.mint(accounts(2), TOTAL_SUPPLY.into(), None);
+        contract.mint(accounts(2), TOTAL_SUPPLY.into(), None);
+        assert_eq!(contract.ft_total_supply().0, 3 * TOTAL_SUPPLY);
+    }
+
+    #[test]
+    fn test_burn() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let burn_amount = TOTAL_SUPPLY / 10;
+        contract.burn(burn_amount.into(), None);
+
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - burn_amount);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - burn_amount);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn test_burn_insufficient_balance() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.burn((TOTAL_SUPPLY + 1).into(), None);
+    }
+
+    #[test]
+    fn test_burn_from() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.burn_from(accounts(1), 40.into(), Some("Cleanup".to_string()));
+
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 40);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 60);
+    }
+
+    #[test]
+    fn test_total_burned_accumulates() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_total_burned().0, 0);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.burn(10.into(), None);
+        contract.burn_from(accounts(1), 40.into(), None);
+        assert_eq!(contract.ft_total_burned().0, 50);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.burn(5.into(), None);
+        assert_eq!(contract.ft_total_burned().0, 55);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 55);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_burn_from_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.burn_from(accounts(2), 1.into(), None);
+    }
 }
@@ -0,0 +1,488 @@
+use crate::events::SettingUpdate;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the minimum amount of a transfer, zero if there is no minimum.
+    pub fn get_min_transfer_amount(&self) -> U128 {
+        self.min_transfer_amount.into()
+    }
+
+    /// Sets the minimum amount of every transfer, including each entry of a batch. Zero disables
+    /// the check. Can only be called by the owner.
+    pub fn set_min_transfer_amount(&mut self, amount: U128) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "min_transfer_amount_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: U128(self.min_transfer_amount),
+                new_value: amount,
+            },
+        );
+        self.min_transfer_amount = amount.0;
+    }
+
+    /// Returns the minimum number of seconds between two transfers sent by the same account,
+    /// zero if there is no cooldown.
+    pub fn get_transfer_cooldown(&self) -> u64 {
+        self.transfer_cooldown_seconds
+    }
+
+    /// Sets the minimum number of seconds between two transfers sent by the same account. Only
+    /// sending is limited, receiving tokens is not. Zero disables the cooldown. Can only be
+    /// called by the owner.
+    pub fn set_transfer_cooldown(&mut self, transfer_cooldown_seconds: u64) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "transfer_cooldown_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.transfer_cooldown_seconds,
+                new_value: transfer_cooldown_seconds,
+            },
+        );
+        self.transfer_cooldown_seconds = transfer_cooldown_seconds;
+    }
+
+    /// Returns `true` if `ft_transfer` to the sender itself is a no-op instead of a panic.
+    pub fn is_self_transfer_allowed(&self) -> bool {
+        self.allow_self_transfer
+    }
+
+    /// Makes `ft_transfer` to the sender itself a no-op without any balance change or event
+    /// when allowed, or a panic otherwise to catch client bugs. Can only be called by the owner.
+    pub fn set_allow_self_transfer(&mut self, allow_self_transfer: bool) {
+        self.assert_owner();
+        self.allow_self_transfer = allow_self_transfer;
+        log!("Self transfers {}", if allow_self_transfer { "allowed" } else { "not allowed" });
+    }
+
+    /// Returns `true` if transfers of zero tokens are allowed.
+    pub fn is_zero_transfer_allowed(&self) -> bool {
+        self.allow_zero_transfers
+    }
+
+    /// Allows transfers of zero tokens, e.g. for protocols that use them as pings, or makes them
+    /// panic, which is the default. Can only be called by the owner.
+    pub fn set_allow_zero_transfers(&mut self, allow_zero_transfers: bool) {
+        self.assert_owner();
+        self.allow_zero_transfers = allow_zero_transfers;
+        log!("Zero transfers {}", if allow_zero_transfers { "allowed" } else { "not allowed" });
+    }
+
+    /// Returns the amount above which a transfer needs the approval of the owner, zero if there
+    /// is no threshold.
+    pub fn get_large_transfer_threshold(&self) -> U128 {
+        self.large_transfer_threshold.into()
+    }
+
+    /// Returns the large transfer amount the owner approved for `sender_id`, if any.
+    pub fn get_large_transfer_approval(&self, sender_id: AccountId) -> Option<U128> {
+        self.large_transfer_approvals.get(&sender_id).map(U128)
+    }
+
+    /// Sets the amount above which every transfer, including each entry of a batch, needs the
+    /// approval of the owner. Zero disables the check. Can only be called by the owner.
+    pub fn set_large_transfer_threshold(&mut self, threshold: U128) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "large_transfer_threshold_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: U128(self.large_transfer_threshold),
+                new_value: threshold,
+            },
+        );
+        self.large_transfer_threshold = threshold.0;
+    }
+
+    /// Approves a single transfer of exactly `amount` tokens from `sender_id` above the large
+    /// transfer threshold, replacing the previous approval of the sender. Can only be called by
+    /// the owner.
+    pub fn approve_large_transfer(&mut self, sender_id: AccountId, amount: U128) {
+        self.assert_owner();
+        self.large_transfer_approvals.insert(&sender_id, &amount.0);
+        log!("Approved a large transfer of {} from @{}", amount.0, sender_id);
+    }
+
+    /// Returns the maximum amount the given account can transfer per day, if any.
+    pub fn get_daily_limit(&self, account_id: AccountId) -> Option<U128> {
+        self.daily_limits.get(&account_id).map(U128)
+    }
+
+    /// Returns the amount the given account transferred since the start of the current day,
+    /// counted only while it has a daily limit.
+    pub fn get_spent_today(&self, account_id: AccountId) -> U128 {
+        match self.spent_today.get(&account_id) {
+            Some((day, spent)) if day == minters::current_day() => spent.into(),
+            _ => 0.into(),
+        }
+    }
+
+    /// Sets the maximum amount the given account can transfer per day, e.g. for custodial
+    /// sub-accounts. Zero removes the limit. Can only be called by the owner.
+    pub fn set_daily_limit(&mut self, account_id: AccountId, limit: U128) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "daily_limit_update",
+            SettingUpdate {
+                account_id: Some(account_id.clone()),
+                old_value: self.get_daily_limit(account_id.clone()),
+                new_value: Some(limit).filter(|limit| limit.0 > 0),
+            },
+        );
+        if limit.0 == 0 {
+            self.daily_limits.remove(&account_id);
+            self.spent_today.remove(&account_id);
+        } else {
+            self.daily_limits.insert(&account_id, &limit.0);
+        }
+    }
+}
+
+impl Contract {
+    /// Panics if `amount` is zero, unless zero transfers are allowed.
+    pub(crate) fn assert_nonzero_transfer(&self, amount: Balance) {
+        scc_assert!(
+            amount > 0 || self.allow_zero_transfers,
+            SccError::ZeroAmount,
+            "Zero transfer not allowed"
+        );
+    }
+
+    pub(crate) fn assert_min_transfer_amount(&self, amount: Balance) {
+        scc_assert!(
+            amount >= self.min_transfer_amount,
+            SccError::BelowMinAmount,
+            "The transfer amount is below the minimum of {}",
+            self.min_transfer_amount
+        );
+    }
+
+    /// Panics if `amount` is above the large transfer threshold and the owner didn't approve a
+    /// transfer of exactly `amount` from `sender_id`, otherwise uses up the approval.
+    pub(crate) fn internal_use_large_transfer_approval(
+        &mut self,
+        sender_id: &AccountId,
+        amount: Balance,
+    ) {
+        if self.large_transfer_threshold == 0 || amount <= self.large_transfer_threshold {
+            return;
+        }
+        scc_assert!(
+            self.large_transfer_approvals.get(sender_id) == Some(amount),
+            SccError::LargeTransferNotApproved,
+            "Transfers above {} need the approval of the owner",
+            self.large_transfer_threshold
+        );
+        self.large_transfer_approvals.remove(sender_id);
+    }
+
+    /// Adds `amount` to the amount `sender_id` transferred today, resetting it when a new day
+    /// starts, and panics if the daily limit of the sender would be exceeded. The fees and burns
+    /// of a transfer count towards the limit, since they leave the balance of the sender.
+    pub(crate) fn internal_record_daily_spend(&mut self, sender_id: &AccountId, amount: Balance) {
+        let limit = match self.daily_limits.get(sender_id) {
+            Some(limit) => limit,
+            None => return,
+        };
+        let day = minters::current_day();
+        let spent = match self.spent_today.get(sender_id) {
+            Some((spent_day, spent)) if spent_day == day => spent,
+            _ => 0,
+        };
+        let spent = spent.checked_add(amount).expect("Balance overflow");
+        scc_assert!(
+            spent <= limit,
+            SccError::DailyLimitExceeded,
+            "The transfer exceeds the daily limit of {} of @{}",
+            limit,
+            sender_id
+        );
+        self.spent_today.insert(sender_id, &(day, spent));
+    }
+
+    /// Panics if the transfer cooldown of `sender_id` has not elapsed yet, otherwise starts a
+    /// new one. A batch counts as a single transfer.
+    pub(crate) fn internal_apply_transfer_cooldown(&mut self, sender_id: &AccountId) {
+        if self.transfer_cooldown_seconds == 0 {
+            return;
+        }
+        let now = env::block_timestamp();
+        if let Some(last_transfer) = self.last_transfer_sent.get(sender_id) {
+            // A huge cooldown saturates instead of overflowing, which would block every transfer.
+            let cooldown_end = last_transfer
+                .saturating_add(self.transfer_cooldown_seconds.saturating_mul(1_000_000_000));
+            scc_assert!(
+                now >= cooldown_end,
+                SccError::CooldownActive,
+                "The transfer cooldown of {} seconds has not elapsed yet",
+                self.transfer_cooldown_seconds
+            );
+        }
+        self.last_transfer_sent.insert(sender_id, &now);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup(min_transfer_amount: Balance) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_min_transfer_amount(min_transfer_amount.into());
+        testing_env!(context.attached_deposit(1).build());
+        (context, contract)
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer amount is below the minimum of 100")]
+    fn test_transfer_below_minimum() {
+        let (_, mut contract) = setup(100);
+        contract.ft_transfer(accounts(1), 99.into(), None);
+    }
+
+    #[test]
+    fn test_transfer_exactly_minimum() {
+        let (_, mut contract) = setup(100);
+        assert_eq!(contract.get_min_transfer_amount().0, 100);
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer amount is below the minimum of 100")]
+    fn test_batch_entry_below_minimum() {
+        let (_, mut contract) = setup(100);
+        contract.ft_transfer_batch(vec![(accounts(1), 100.into()), (accounts(1), 1.into())], None);
+    }
+
+    #[test]
+    fn test_zero_minimum_disables_check() {
+        let (_, mut contract) = setup(0);
+        contract.ft_transfer(accounts(1), 1.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1);
+    }
+
+    fn setup_cooldown(transfer_cooldown_seconds: u64) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_transfer_cooldown(transfer_cooldown_seconds);
+        testing_env!(context.attached_deposit(1).block_timestamp(1_000_000_000).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        (context, contract)
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer cooldown of 60 seconds has not elapsed yet")]
+    fn test_transfer_during_cooldown() {
+        let (mut context, mut contract) = setup_cooldown(60);
+        testing_env!(context.block_timestamp(60_000_000_000).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    fn test_transfer_after_cooldown() {
+        let (mut context, mut contract) = setup_cooldown(60);
+        assert_eq!(contract.get_transfer_cooldown(), 60);
+
+        // Receiving tokens is not limited by the cooldown of the receiver.
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(2), 10.into(), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(61_000_000_000)
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 190);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_COOLDOWN_ACTIVE: The transfer cooldown of 18446744073709551615")]
+    fn test_transfer_during_max_cooldown() {
+        let (mut context, mut contract) = setup_cooldown(u64::MAX);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100);
+        testing_env!(context.block_timestamp(u64::MAX - 1).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+    }
+
+    #[test]
+    fn test_zero_cooldown_disables_check() {
+        let (_, mut contract) = setup_cooldown(0);
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 200);
+    }
+
+    fn setup_large_transfer(threshold: Balance) -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_large_transfer_threshold(threshold.into());
+        testing_env!(context.attached_deposit(1).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_approved_large_transfer() {
+        let (mut context, mut contract) = setup_large_transfer(1_000);
+        assert_eq!(contract.get_large_transfer_threshold().0, 1_000);
+        contract.ft_transfer(accounts(1), 1_000.into(), None);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.approve_large_transfer(accounts(2), 5_000.into());
+        assert_eq!(contract.get_large_transfer_approval(accounts(2)), Some(U128(5_000)));
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5_000.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 6_000);
+        assert_eq!(contract.get_large_transfer_approval(accounts(2)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers above 1000 need the approval of the owner")]
+    fn test_unapproved_large_transfer() {
+        let (_, mut contract) = setup_large_transfer(1_000);
+        contract.ft_transfer(accounts(1), 1_001.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers above 1000 need the approval of the owner")]
+    fn test_large_transfer_approval_amount_mismatch() {
+        let (mut context, mut contract) = setup_large_transfer(1_000);
+        testing_env!(context.attached_deposit(0).build());
+        contract.approve_large_transfer(accounts(2), 5_000.into());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 4_000.into(), None);
+    }
+
+    #[test]
+    fn test_zero_large_transfer_threshold_disables_check() {
+        let (_, mut contract) = setup_large_transfer(0);
+        contract.ft_transfer(accounts(1), 1_000_000.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_000_000);
+    }
+
+    #[test]
+    fn test_allowed_self_transfer() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_allow_self_transfer(true);
+        assert!(contract.is_self_transfer_allowed());
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(2), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY);
+        assert!(get_logs().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender and receiver should be different")]
+    fn test_disallowed_self_transfer() {
+        let (_, mut contract) = setup(0);
+        assert!(!contract.is_self_transfer_allowed());
+        contract.ft_transfer(accounts(2), 100.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Zero transfer not allowed")]
+    fn test_zero_transfer() {
+        let (_, mut contract) = setup(0);
+        contract.ft_transfer(accounts(1), 0.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Zero transfer not allowed")]
+    fn test_zero_transfer_call() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+        contract.ft_transfer_call(accounts(1), 0.into(), None, "ping".to_string());
+    }
+
+    #[test]
+    fn test_allowed_zero_transfer() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_allow_zero_transfers(true);
+        assert!(contract.is_zero_transfer_allowed());
+
+        testing_env!(context.attached_deposit(1).prepaid_gas(Gas(300_000_000_000_000)).build());
+        contract.ft_transfer(accounts(1), 0.into(), None);
+        contract.ft_transfer_call(accounts(1), 0.into(), None, "ping".to_string());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY);
+    }
+
+    #[test]
+    fn test_daily_limit() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_daily_limit(accounts(2), 100.into());
+        assert_eq!(contract.get_daily_limit(accounts(2)), Some(U128(100)));
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 60.into(), None);
+        contract.ft_transfer(accounts(1), 40.into(), None);
+        assert_eq!(contract.get_spent_today(accounts(2)).0, 100);
+
+        // The next day starts with a fresh limit.
+        testing_env!(context.block_timestamp(minters::NANOSECONDS_PER_DAY).build());
+        assert_eq!(contract.get_spent_today(accounts(2)).0, 0);
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "The transfer exceeds the daily limit of 100 of @charlie")]
+    fn test_daily_limit_exceeded() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_daily_limit(accounts(2), 100.into());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 60.into(), None);
+        contract.ft_transfer(accounts(1), 41.into(), None);
+    }
+
+    #[test]
+    fn test_daily_limit_removed() {
+        let (mut context, mut contract) = setup(0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_daily_limit(accounts(2), 100.into());
+        contract.set_daily_limit(accounts(2), 0.into());
+        assert_eq!(contract.get_daily_limit(accounts(2)), None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 1_000.into(), None);
+        assert_eq!(contract.get_spent_today(accounts(2)).0, 0);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::events::SettingUpdate;
+use crate::*;
+use near_sdk::serde_json::{self, Map, Value};
+
+/// The maximum length of a memo that is parsed as a structured memo.
+const MAX_STRUCTURED_MEMO_LEN: usize = 1024;
+/// The maximum number of line items of a structured memo.
+const MAX_MEMO_ITEMS: usize = 20;
+/// The default maximum length of a memo in bytes.
+pub(crate) const DEFAULT_MAX_MEMO_BYTES: u32 = 256;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if `ft_transfer` and `ft_transfer_call` require a non-empty memo.
+    pub fn is_memo_required(&self) -> bool {
+        self.require_memo
+    }
+
+    /// Enables or disables the requirement of a non-empty memo on `ft_transfer` and
+    /// `ft_transfer_call`. Can only be called by the owner.
+    pub fn set_require_memo(&mut self, require_memo: bool) {
+        self.assert_owner();
+        self.require_memo = require_memo;
+        log!("Memo {}", if require_memo { "required" } else { "not required" });
+    }
+
+    /// Returns the maximum length in bytes of the memos of transfers, mints and burns.
+    pub fn get_max_memo_bytes(&self) -> u32 {
+        self.max_memo_bytes
+    }
+
+    /// Sets the maximum length in bytes of the memos of transfers, mints and burns. Can only be
+    /// called by the owner.
+    pub fn set_max_memo_bytes(&mut self, max_memo_bytes: u32) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "max_memo_bytes_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.max_memo_bytes,
+                new_value: max_memo_bytes,
+            },
+        );
+        self.max_memo_bytes = max_memo_bytes;
+    }
+}
+
+impl Contract {
+    /// Panics if a memo is required and the given one is missing or empty.
+    pub(crate) fn assert_memo_present(&self, memo: Option<&str>) {
+        if self.require_memo {
+            scc_assert!(
+                memo.map_or(false, |memo| !memo.is_empty()),
+                SccError::MemoRequired,
+                "A memo is required for transfers"
+            );
+        }
+    }
+
+    /// Panics if the given memo is longer than the maximum length or contains control
+    /// characters, which the indexers would otherwise have to escape.
+    pub(crate) fn assert_valid_memo(&self, memo: Option<&str>) {
+        if let Some(memo) = memo {
+            scc_assert!(
+                memo.len() <= self.max_memo_bytes as usize,
+                SccError::InvalidMemo,
+                "The memo can't be longer than {} bytes",
+                self.max_memo_bytes
+            );
+            scc_assert!(
+                !memo.chars().any(char::is_control),
+                SccError::InvalidMemo,
+                "The memo can't contain control characters"
+            );
+        }
+    }
+}
+
+/// Parses a structured memo, a JSON object with an `items` array of line item objects, e.g.
+/// `{"reference":"INV-42","items":[{"description":"Rent","amount":"100"}]}`. Returns `None` for
+/// memos that are too long or not structured, which are treated as plain text.
+pub(crate) fn parse_structured_memo(memo: &str) -> Option<Map<String, Value>> {
+    if memo.len() > MAX_STRUCTURED_MEMO_LEN {
+        return None;
+    }
+    let memo: Map<String, Value> = serde_json::from_str(memo).ok()?;
+    let items = memo.get("items")?.as_array()?;
+    if items.is_empty() || items.len() > MAX_MEMO_ITEMS || !items.iter().all(Value::is_object) {
+        return None;
+    }
+    Some(memo)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_require_memo(true);
+        assert!(contract.is_memo_required());
+        testing_env!(context.attached_deposit(1).build());
+        (context, contract)
+    }
+
+    #[test]
+    #[should_panic(expected = "A memo is required for transfers")]
+    fn test_transfer_without_memo() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(1), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "A memo is required for transfers")]
+    fn test_transfer_call_with_empty_memo() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+        contract.ft_transfer_call(accounts(1), 10.into(), Some("".to_string()), "".to_string());
+    }
+
+    #[test]
+    fn test_transfer_with_memo() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(1), 10.into(), Some("Invoice 42".to_string()));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+    }
+
+    #[test]
+    fn test_parse_structured_memo() {
+        assert!(parse_structured_memo("Invoice 42").is_none());
+        assert!(parse_structured_memo(r#"{"reference":"INV-42"}"#).is_none());
+        assert!(parse_structured_memo(r#"{"items":[]}"#).is_none());
+        assert!(parse_structured_memo(r#"{"items":["Rent"]}"#).is_none());
+        assert!(parse_structured_memo(r#"{"items":[{"description":"Rent"}"#).is_none());
+        let long_memo = format!(r#"{{"items":[{{"description":"{}"}}]}}"#, "a".repeat(1024));
+        assert!(parse_structured_memo(&long_memo).is_none());
+        assert!(parse_structured_memo(r#"{"items":[{"description":"Rent"}]}"#).is_some());
+    }
+
+    #[test]
+    fn test_plain_memo_event() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), Some("Invoice 42".to_string()));
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 1);
+        let event: Value =
+            serde_json::from_str(logs[0].strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+        assert_eq!(event["standard"], "nep141");
+        assert_eq!(event["data"][0]["memo"], "Invoice 42");
+    }
+
+    #[test]
+    fn test_structured_memo_event() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        let memo = json!({
+            "reference": "INV-42",
+            "items": [
+                {"description": "Rent", "amount": "7"},
+                {"description": "Utilities", "amount": "3"}
+            ]
+        });
+        contract.ft_transfer(accounts(1), 10.into(), Some(memo.to_string()));
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 2);
+        let event: Value =
+            serde_json::from_str(logs[1].strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "standard": "scc",
+                "version": "1.0.0",
+                "event": "ft_transfer",
+                "data": [{
+                    "old_owner_id": accounts(2),
+                    "new_owner_id": accounts(1),
+                    "amount": "10",
+                    "memo": memo
+                }]
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The memo can't be longer than 256 bytes")]
+    fn test_transfer_with_long_memo() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(1), 10.into(), Some("a".repeat(257)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The memo can't contain control characters")]
+    fn test_transfer_with_control_characters() {
+        let (_, mut contract) = setup();
+        contract.ft_transfer(accounts(1), 10.into(), Some("Invoice\n42".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The memo can't be longer than 16 bytes")]
+    fn test_mint_with_long_memo() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_max_memo_bytes(16);
+        assert_eq!(contract.get_max_memo_bytes(), 16);
+        contract.mint(accounts(1), 10.into(), Some("Quarterly rewards".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "The memo can't contain control characters")]
+    fn test_burn_with_control_characters() {
+        let (_, mut contract) = setup();
+        contract.burn(10.into(), Some("Burn\u{1b}[0m".to_string()));
+    }
+}
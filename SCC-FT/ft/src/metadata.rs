@@ -0,0 +1,397 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde_json::{json, Map, Value};
+
+/// The maximum number of decimals, matching the precision of NEAR itself.
+const MAX_DECIMALS: u8 = 24;
+/// The maximum length of the symbol in bytes, so wallets can display it.
+const MAX_SYMBOL_LEN: usize = 16;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the number of decimals of the token.
+    pub fn ft_decimals(&self) -> u8 {
+        self.metadata.get().unwrap().decimals
+    }
+
+    /// Returns the balance of the given account as a decimal number according to the token
+    /// `decimals`, e.g. "1234.56789000".
+    pub fn ft_balance_of_display(&self, account_id: AccountId) -> String {
+        format_amount(self.internal_balance_of(&account_id), self.ft_decimals())
+    }
+
+    /// Returns the total supply as a decimal number according to the token `decimals` with the
+    /// integer part grouped by thousands, e.g. "10,000,000.00000000".
+    pub fn ft_total_supply_display(&self) -> String {
+        let amount = format_amount(self.token.total_supply, self.ft_decimals());
+        let (integer, fraction) = amount.split_at(amount.find('.').unwrap_or(amount.len()));
+        format!("{}{}", group_thousands(integer), fraction)
+    }
+
+    /// Replaces the token metadata. The `decimals` can't be changed after the deployment.
+    /// Can only be called by the owner.
+    pub fn set_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        self.assert_owner();
+        self.internal_set_metadata(metadata);
+    }
+
+    /// Replaces the token icon. Can only be called by the owner.
+    pub fn set_icon(&mut self, icon: Option<String>) {
+        self.assert_owner();
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.icon = icon;
+        self.internal_set_metadata(metadata);
+    }
+
+    /// Replaces the off-chain metadata reference and the sha256 hash of its content, which are
+    /// either both set or both unset. Can only be called by the owner.
+    pub fn set_reference(
+        &mut self,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    ) {
+        self.assert_owner();
+        assert_valid_reference(&reference, &reference_hash);
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.reference = reference;
+        metadata.reference_hash = reference_hash;
+        self.internal_set_metadata(metadata);
+    }
+
+    /// Validates the stored metadata and upgrades an outdated `spec` to the one of the current
+    /// standard. Returns `true` if the metadata was repaired, calling it again does nothing.
+    /// Can only be called by the owner.
+    pub fn validate_and_repair_metadata(&mut self) -> bool {
+        self.assert_owner();
+        let mut metadata = self.metadata.get().unwrap();
+        if metadata.spec == FT_METADATA_SPEC {
+            metadata.assert_valid();
+            return false;
+        }
+        metadata.spec = FT_METADATA_SPEC.to_string();
+        self.internal_set_metadata(metadata);
+        true
+    }
+}
+
+pub(crate) fn assert_valid_decimals(decimals: u8) {
+    assert!(decimals <= MAX_DECIMALS, "The decimals can't exceed {}", MAX_DECIMALS);
+}
+
+pub(crate) fn assert_valid_symbol(symbol: &str) {
+    assert!(
+        !symbol.is_empty() && symbol.len() <= MAX_SYMBOL_LEN,
+        "The symbol should be between 1 and {} bytes long",
+        MAX_SYMBOL_LEN
+    );
+}
+
+/// Panics unless the reference and its 32-byte hash are either both set or both unset.
+fn assert_valid_reference(reference: &Option<String>, reference_hash: &Option<Base64VecU8>) {
+    match (reference, reference_hash) {
+        (Some(_), None) => env::panic_str("The reference hash is required with a reference"),
+        (None, Some(_)) => env::panic_str("The reference hash can't be set without a reference"),
+        (_, Some(reference_hash)) => {
+            assert_eq!(reference_hash.0.len(), 32, "The reference hash should be 32 bytes")
+        }
+        (None, None) => {}
+    }
+}
+
+/// Formats `amount` as a decimal number with `decimals` fractional digits.
+pub(crate) fn format_amount(amount: Balance, decimals: u8) -> String {
+    let digits = format!("{:0>width$}", amount, width = decimals as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Separates every group of three digits of the `integer` digits with a comma.
+fn group_thousands(integer: &str) -> String {
+    let mut grouped = String::with_capacity(integer.len() + integer.len() / 3);
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+impl Contract {
+    fn internal_set_metadata(&mut self, metadata: FungibleTokenMetadata) {
+        metadata.assert_valid();
+        let old_metadata = self.metadata.get().unwrap();
+        assert_eq!(
+            metadata.decimals, old_metadata.decimals,
+            "The decimals can't be changed after the deployment"
+        );
+        self.metadata.set(&metadata);
+
+        let mut changes = Map::new();
+        let mut record_change = |field: &str, old: Value, new: Value| {
+            if old != new {
+                changes.insert(field.to_string(), new);
+            }
+        };
+        record_change("spec", json!(old_metadata.spec), json!(metadata.spec));
+        record_change("name", json!(old_metadata.name), json!(metadata.name));
+        record_change("symbol", json!(old_metadata.symbol), json!(metadata.symbol));
+        record_change("icon", json!(old_metadata.icon), json!(metadata.icon));
+        record_change("reference", json!(old_metadata.reference), json!(metadata.reference));
+        record_change(
+            "reference_hash",
+            json!(old_metadata.reference_hash),
+            json!(metadata.reference_hash),
+        );
+        self.emit_event("metadata_update", &[changes]);
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::serde_json;
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, test_metadata, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(0, 8), "0.00000000");
+        assert_eq!(format_amount(1, 8), "0.00000001");
+        assert_eq!(format_amount(100_000_000, 8), "1.00000000");
+        assert_eq!(format_amount(123_456_789_000, 8), "1234.56789000");
+        assert_eq!(format_amount(42, 0), "42");
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands("0"), "0");
+        assert_eq!(group_thousands("999"), "999");
+        assert_eq!(group_thousands("1000"), "1,000");
+        assert_eq!(group_thousands("10000000"), "10,000,000");
+        assert_eq!(
+            group_thousands(&u128::MAX.to_string()),
+            "340,282,366,920,938,463,463,374,607,431,768,211,455"
+        );
+    }
+
+    #[test]
+    fn test_total_supply_display() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                1_000_000_000_000_000.into(),
+                test_metadata(),
+                None,
+                None,
+                None,
+                None,
+            )
+        });
+        assert_eq!(contract.ft_total_supply_display(), "10,000,000.00000000");
+
+        let mut metadata = test_metadata();
+        metadata.decimals = 0;
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new(accounts(2), 1_234_567.into(), metadata, None, None, None, None)
+        });
+        assert_eq!(contract.ft_total_supply_display(), "1,234,567");
+    }
+
+    #[test]
+    fn test_balance_of_display() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                123_456_789_000.into(),
+                test_metadata(),
+                None,
+                None,
+                None,
+                None,
+            )
+        });
+        assert_eq!(contract.ft_balance_of_display(accounts(2)), "1234.56789000");
+        assert_eq!(contract.ft_balance_of_display(accounts(1)), "0.00000000");
+    }
+
+    #[test]
+    fn test_set_metadata() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut metadata = contract.ft_metadata();
+        metadata.name = "Socialverse City Token".to_string();
+        contract.set_metadata(metadata);
+        contract.set_icon(Some("data:image/svg+xml,<svg></svg>".to_string()));
+
+        testing_env!(context.is_view(true).build());
+        let metadata = contract.ft_metadata();
+        assert_eq!(metadata.name, "Socialverse City Token");
+        assert_eq!(metadata.icon, Some("data:image/svg+xml,<svg></svg>".to_string()));
+        assert_eq!(metadata.decimals, 8);
+    }
+
+    #[test]
+    fn test_metadata_update_event() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut metadata = contract.ft_metadata();
+        metadata.name = "Socialverse City Token".to_string();
+        metadata.symbol = "SCT".to_string();
+        testing_env!(context.build());
+        contract.set_metadata(metadata);
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 1);
+        let event: Value =
+            serde_json::from_str(logs[0].strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+        assert_eq!(
+            event,
+            json!({
+                "standard": "scc",
+                "version": "1.0.0",
+                "event": "metadata_update",
+                "data": [{"name": "Socialverse City Token", "symbol": "SCT"}]
+            })
+        );
+
+        testing_env!(context.build());
+        contract.set_icon(None);
+        let logs = get_logs();
+        assert_eq!(
+            logs,
+            vec![
+                r#"EVENT_JSON:{"standard":"scc","version":"1.0.0","event":"metadata_update","data":[{"icon":null}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_reference() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let reference_hash = Base64VecU8(env::sha256(b"reference"));
+        contract.set_reference(
+            Some("https://example.com/scc.json".to_string()),
+            Some(reference_hash.clone()),
+        );
+
+        let metadata = contract.ft_metadata();
+        assert_eq!(metadata.reference, Some("https://example.com/scc.json".to_string()));
+        assert_eq!(metadata.reference_hash, Some(reference_hash));
+
+        contract.set_reference(None, None);
+        assert_eq!(contract.ft_metadata().reference, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reference hash is required with a reference")]
+    fn test_set_reference_without_hash() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_reference(Some("https://example.com/scc.json".to_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reference hash should be 32 bytes")]
+    fn test_set_reference_wrong_hash_length() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_reference(
+            Some("https://example.com/scc.json".to_string()),
+            Some(Base64VecU8(vec![0; 31])),
+        );
+    }
+
+    #[test]
+    fn test_repair_stale_spec() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut metadata = contract.ft_metadata();
+        metadata.spec = "ft-0.9.0".to_string();
+        contract.metadata.set(&metadata);
+
+        assert!(contract.validate_and_repair_metadata());
+        assert_eq!(contract.ft_metadata().spec, FT_METADATA_SPEC);
+        assert!(!contract.validate_and_repair_metadata());
+        assert_eq!(contract.ft_metadata().spec, FT_METADATA_SPEC);
+    }
+
+    #[test]
+    #[should_panic(expected = "The decimals can't be changed after the deployment")]
+    fn test_set_metadata_decimals() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut metadata = contract.ft_metadata();
+        metadata.decimals = 18;
+        contract.set_metadata(metadata);
+    }
+
+    #[test]
+    fn test_decimals() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut metadata = test_metadata();
+        metadata.decimals = 24;
+        let contract = init_contract(&context, || {
+            Contract::new(accounts(2), TOTAL_SUPPLY.into(), metadata, None, None, None, None)
+        });
+        assert_eq!(contract.ft_decimals(), 24);
+    }
+
+    #[test]
+    #[should_panic(expected = "The decimals can't exceed 24")]
+    fn test_too_many_decimals() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut metadata = test_metadata();
+        metadata.decimals = 100;
+        init_contract(&context, || {
+            Contract::new(accounts(2), TOTAL_SUPPLY.into(), metadata, None, None, None, None)
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_set_icon_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_icon(None);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::*;
+
+/// The state layout of the initial release, before the owner and the admin settings were added.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub(crate) struct ContractV1 {
+    token: FungibleToken,
+    metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Migrates the state of the initial release to the current layout, keeping all balances
+    /// and the metadata. The contract account becomes the owner and can hand the ownership over
+    /// with `propose_new_owner`; every other new setting starts at its default value. The old
+    /// layout doesn't record the deployment, so the migration is recorded as the deployment.
+    ///
+    /// Accounts registered before the migration are not listed by `get_accounts` until they
+    /// register again, because the old layout doesn't keep an iterable list of accounts. For the
+    /// same reason, `ft_holders_count` doesn't count the accounts that already held tokens
+    /// before the migration.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old_state: ContractV1 = env::state_read().expect("The old state doesn't exist");
+        Self::internal_new(
+            env::current_account_id(),
+            old_state.token,
+            old_state.metadata,
+            None,
+            timelock::DEFAULT_TIMELOCK_DELAY,
+        )
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, test_metadata, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let context = get_context(accounts(0));
+        testing_env!(context.build());
+        let mut token = FungibleToken::new(b"a".to_vec());
+        token.internal_register_account(&accounts(1));
+        token.internal_deposit(&accounts(1), TOTAL_SUPPLY);
+        let old_state =
+            ContractV1 { token, metadata: LazyOption::new(b"m".to_vec(), Some(&test_metadata())) };
+        env::state_write(&old_state);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, TOTAL_SUPPLY);
+        assert_eq!(contract.ft_metadata().symbol, "TEST");
+        assert!(!contract.is_paused());
+        assert_eq!(contract.get_max_supply(), None);
+        assert_eq!(contract.get_deployment_info(), (accounts(0), U64(0)));
+        assert_eq!(contract.get_transfer_stats(), (U128(0), 0));
+    }
+}
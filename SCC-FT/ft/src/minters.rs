@@ -0,0 +1,225 @@
+use crate::events::SettingUpdate;
+use crate::*;
+
+pub(crate) const NANOSECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if the given account is allowed to mint tokens.
+    pub fn is_minter(&self, account_id: AccountId) -> bool {
+        self.minters.contains(&account_id)
+    }
+
+    /// Returns all accounts that are allowed to mint tokens besides the owner.
+    pub fn get_minters(&self) -> Vec<AccountId> {
+        self.minters.to_vec()
+    }
+
+    /// Grants the minter role to the given account. Can only be called by the owner.
+    pub fn add_minter(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(self.minters.insert(&account_id), "The account {} is already a minter", account_id);
+        self.emit_admin_event(
+            "minter_update",
+            SettingUpdate { account_id: Some(account_id), old_value: false, new_value: true },
+        );
+    }
+
+    /// Revokes the minter role from the given account. Can only be called by the owner.
+    pub fn remove_minter(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(self.minters.remove(&account_id), "The account {} is not a minter", account_id);
+        self.emit_admin_event(
+            "minter_update",
+            SettingUpdate { account_id: Some(account_id), old_value: true, new_value: false },
+        );
+    }
+
+    /// Returns the maximum amount that can be minted per day, if any.
+    pub fn get_mint_limit(&self) -> Option<U128> {
+        self.mint_limit_per_day.map(U128)
+    }
+
+    /// Returns the amount minted since the start of the current day.
+    pub fn get_minted_today(&self) -> U128 {
+        if self.current_day == current_day() {
+            self.minted_today.into()
+        } else {
+            0.into()
+        }
+    }
+
+    /// Sets the maximum amount that can be minted per day, or removes the limit with `None`.
+    /// Can only be called by the owner.
+    pub fn set_mint_limit(&mut self, limit: Option<U128>) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "mint_limit_update",
+            SettingUpdate { account_id: None, old_value: self.get_mint_limit(), new_value: limit },
+        );
+        self.mint_limit_per_day = limit.map(|limit| limit.0);
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_minter(&self) {
+        let account_id = env::predecessor_account_id();
+        scc_assert!(
+            self.is_owner_call() || self.minters.contains(&account_id),
+            SccError::NotMinter,
+            "Only the owner or a minter can call this method"
+        );
+    }
+
+    /// Adds `amount` to the amount minted today, resetting it when a new day starts, and panics
+    /// if the daily mint limit would be exceeded.
+    pub(crate) fn internal_record_daily_mint(&mut self, amount: Balance) {
+        let day = current_day();
+        if self.current_day != day {
+            self.current_day = day;
+            self.minted_today = 0;
+        }
+        let minted_today = self.minted_today.checked_add(amount).expect("Balance overflow");
+        if let Some(limit) = self.mint_limit_per_day {
+            scc_assert!(
+                minted_today <= limit,
+                SccError::MintLimitExceeded,
+                "Minting would exceed the daily mint limit"
+            );
+        }
+        self.minted_today = minted_today;
+    }
+}
+
+pub(crate) fn current_day() -> u64 {
+    env::block_timestamp() / NANOSECONDS_PER_DAY
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_minter_role() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.add_minter(accounts(3));
+        assert!(contract.is_minter(accounts(3)));
+        assert_eq!(contract.get_minters(), vec![accounts(3)]);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.mint(accounts(2), 10.into(), None);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or a minter can call this method")]
+    fn test_removed_minter_cannot_mint() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.add_minter(accounts(3));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.mint(accounts(2), 10.into(), None);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.remove_minter(accounts(3));
+        assert!(!contract.is_minter(accounts(3)));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.mint(accounts(2), 10.into(), None);
+    }
+
+    #[test]
+    fn test_admin_events() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+
+        testing_env!(context.build());
+        contract.add_minter(accounts(3));
+        contract.remove_minter(accounts(3));
+        assert_eq!(
+            get_logs(),
+            vec![
+                format!(
+                    r#"EVENT_JSON:{{"standard":"scc_admin","version":"1.0.0","event":"minter_update","data":[{{"account_id":"{}","old_value":false,"new_value":true}}]}}"#,
+                    accounts(3)
+                ),
+                format!(
+                    r#"EVENT_JSON:{{"standard":"scc_admin","version":"1.0.0","event":"minter_update","data":[{{"account_id":"{}","old_value":true,"new_value":false}}]}}"#,
+                    accounts(3)
+                ),
+            ]
+        );
+
+        testing_env!(context.build());
+        contract.set_mint_limit(Some(100.into()));
+        contract.set_mint_limit(None);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"scc_admin","version":"1.0.0","event":"mint_limit_update","data":[{"old_value":null,"new_value":"100"}]}"#,
+                r#"EVENT_JSON:{"standard":"scc_admin","version":"1.0.0","event":"mint_limit_update","data":[{"old_value":"100","new_value":null}]}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_daily_mint_limit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(NANOSECONDS_PER_DAY).build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_mint_limit(Some(100.into()));
+        assert_eq!(contract.get_mint_limit(), Some(100.into()));
+
+        contract.mint(accounts(2), 60.into(), None);
+        contract.mint(accounts(2), 40.into(), None);
+        assert_eq!(contract.get_minted_today().0, 100);
+
+        testing_env!(context.block_timestamp(2 * NANOSECONDS_PER_DAY).build());
+        assert_eq!(contract.get_minted_today().0, 0);
+        contract.mint(accounts(2), 100.into(), None);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 200);
+    }
+
+    #[test]
+    #[should_panic(expected = "Minting would exceed the daily mint limit")]
+    fn test_daily_mint_limit_exceeded() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.block_timestamp(NANOSECONDS_PER_DAY).build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_mint_limit(Some(100.into()));
+        contract.mint(accounts(2), 60.into(), None);
+        testing_env!(context.block_timestamp(2 * NANOSECONDS_PER_DAY - 1).build());
+        contract.mint(accounts(2), 41.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_add_minter_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.add_minter(accounts(3));
+    }
+}
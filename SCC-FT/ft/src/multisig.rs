@@ -0,0 +1,274 @@
+//! Multisig governance of the privileged methods.
+//!
+//! By default the contract has a single owner, `owner_id`, who calls the privileged methods
+//! directly, which is the same as a multisig of one owner with a threshold of 1. Once the owner
+//! configures several owners with a threshold above 1, the privileged methods can only be called
+//! by the contract itself: an owner proposes a call, the other owners confirm it, and once it
+//! reaches the threshold any owner executes it as a function call from the contract to itself.
+use crate::*;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The maximum number of multisig owners.
+const MAX_MULTISIG_OWNERS: usize = 10;
+/// The gas kept by `execute` for itself, the rest is attached to the proposed call.
+const GAS_FOR_EXECUTE: Gas = Gas(10_000_000_000_000);
+/// The minimum gas attached to the proposed call.
+const MIN_GAS_FOR_PROPOSED_CALL: Gas = Gas(5_000_000_000_000);
+/// Methods a proposal can't call. The private callbacks trust the contract as the predecessor to
+/// pass the results of its own promises, so a proposal could call them with made-up arguments,
+/// and `burn` would act on the tokens held by the contract account.
+const FORBIDDEN_PROPOSAL_METHODS: [&str; 9] = [
+    "ft_resolve_transfer",
+    "ft_resolve_transfer_batch",
+    "on_transfer_hook_checked",
+    "on_denylist_fetched",
+    "on_ft_rescued",
+    "on_buyback_paid",
+    "burn",
+    "migrate",
+    "execute",
+];
+
+/// A proposed call of a privileged method with its JSON arguments.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub method_name: String,
+    pub args: String,
+    pub confirmations: Vec<AccountId>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the owners and the number of confirmations a proposal needs.
+    pub fn get_multisig(&self) -> (Vec<AccountId>, u8) {
+        (self.internal_multisig_owners(), self.multisig_threshold)
+    }
+
+    /// Returns the proposal with the given id, if it wasn't executed yet.
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(&proposal_id)
+    }
+
+    /// Replaces the owners allowed to propose and confirm privileged calls, and the number of
+    /// confirmations needed. A single owner with a threshold of 1 restores the direct calls
+    /// by `owner_id`. Can only be called by the owner.
+    pub fn set_multisig(&mut self, owners: Vec<AccountId>, threshold: u8) {
+        self.assert_owner();
+        assert!(!owners.is_empty(), "The multisig needs at least one owner");
+        assert!(
+            owners.len() <= MAX_MULTISIG_OWNERS,
+            "The multisig can't have more than {} owners",
+            MAX_MULTISIG_OWNERS
+        );
+        assert!(
+            threshold >= 1 && threshold as usize <= owners.len(),
+            "The threshold should be between 1 and the number of owners"
+        );
+        for (index, owner) in owners.iter().enumerate() {
+            assert!(!owners[..index].contains(owner), "The owner {} is duplicated", owner);
+        }
+        log!("Multisig set to {} of {} owners", threshold, owners.len());
+        self.multisig_owners = owners;
+        self.multisig_threshold = threshold;
+    }
+
+    /// Proposes to call `method_name` on the contract with the JSON `args`, confirmed by the
+    /// caller. Returns the id of the proposal. Can only be called by a multisig owner.
+    pub fn propose(&mut self, method_name: String, args: String) -> u64 {
+        let account_id = self.assert_multisig_owner();
+        assert!(
+            !FORBIDDEN_PROPOSAL_METHODS.contains(&method_name.as_str()),
+            "The method {} can't be proposed",
+            method_name
+        );
+        let proposal_id = self.proposal_count;
+        self.proposal_count += 1;
+        self.proposals
+            .insert(&proposal_id, &Proposal { method_name, args, confirmations: vec![account_id] });
+        log!("Proposal {} created", proposal_id);
+        proposal_id
+    }
+
+    /// Confirms the given proposal. Can only be called by a multisig owner.
+    pub fn confirm(&mut self, proposal_id: u64) {
+        let account_id = self.assert_multisig_owner();
+        let mut proposal = self.internal_get_proposal(proposal_id);
+        assert!(
+            !proposal.confirmations.contains(&account_id),
+            "The proposal {} is already confirmed by @{}",
+            proposal_id,
+            account_id
+        );
+        proposal.confirmations.push(account_id);
+        self.proposals.insert(&proposal_id, &proposal);
+        log!("Proposal {} confirmed", proposal_id);
+    }
+
+    /// Executes the given proposal once enough of the current owners confirmed it, by calling
+    /// the proposed method from the contract account with the gas left. Can only be called by a
+    /// multisig owner.
+    pub fn execute(&mut self, proposal_id: u64) -> Promise {
+        self.assert_multisig_owner();
+        let required_gas = env::used_gas() + GAS_FOR_EXECUTE + MIN_GAS_FOR_PROPOSED_CALL;
+        assert!(
+            env::prepaid_gas() >= required_gas,
+            "More gas is required, at least {} is needed",
+            required_gas.0
+        );
+        let proposal = self.internal_get_proposal(proposal_id);
+        let owners = self.internal_multisig_owners();
+        let confirmations =
+            proposal.confirmations.iter().filter(|account_id| owners.contains(account_id)).count();
+        assert!(
+            confirmations >= self.multisig_threshold as usize,
+            "The proposal {} has {} of {} required confirmations",
+            proposal_id,
+            confirmations,
+            self.multisig_threshold
+        );
+        self.proposals.remove(&proposal_id);
+        log!("Proposal {} executed", proposal_id);
+        Promise::new(env::current_account_id()).function_call(
+            proposal.method_name,
+            proposal.args.into_bytes(),
+            0,
+            env::prepaid_gas() - env::used_gas() - GAS_FOR_EXECUTE,
+        )
+    }
+}
+
+impl Contract {
+    /// Returns `true` if the current call is allowed to use the privileged methods.
+    pub(crate) fn is_owner_call(&self) -> bool {
+        if self.ownership_renounced {
+            return false;
+        }
+        let account_id = env::predecessor_account_id();
+        account_id == env::current_account_id()
+            || (self.multisig_threshold <= 1 && account_id == self.owner_id)
+    }
+
+    fn internal_multisig_owners(&self) -> Vec<AccountId> {
+        if self.multisig_owners.is_empty() {
+            vec![self.owner_id.clone()]
+        } else {
+            self.multisig_owners.clone()
+        }
+    }
+
+    fn internal_get_proposal(&self, proposal_id: u64) -> Proposal {
+        self.proposals.get(&proposal_id).unwrap_or_else(|| {
+            env::panic_str(&format!("The proposal {} doesn't exist", proposal_id))
+        })
+    }
+
+    fn assert_multisig_owner(&self) -> AccountId {
+        let account_id = env::predecessor_account_id();
+        assert!(
+            self.internal_multisig_owners().contains(&account_id),
+            "Only a multisig owner can call this method"
+        );
+        account_id
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    /// Sets up a 2-of-3 multisig of alice, bob and charlie, with charlie as the original owner.
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.prepaid_gas(Gas(300_000_000_000_000)).build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_multisig(vec![accounts(0), accounts(1), accounts(2)], 2);
+        (context, contract)
+    }
+
+    #[test]
+    fn test_single_owner_is_threshold_one() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        assert_eq!(contract.get_multisig(), (vec![accounts(2)], 1));
+    }
+
+    #[test]
+    fn test_two_of_three_confirmation() {
+        let (mut context, mut contract) = setup();
+        let proposal_id = contract.propose("pause".to_string(), "{}".to_string());
+
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.confirm(proposal_id);
+        assert_eq!(
+            contract.get_proposal(proposal_id).unwrap().confirmations,
+            vec![accounts(2), accounts(1)]
+        );
+        contract.execute(proposal_id);
+        assert_eq!(contract.get_proposal(proposal_id), None);
+
+        // `execute` calls the proposed method on the contract itself.
+        let receipts = get_created_receipts();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receiver_id.as_str(), accounts(0).as_str());
+        let actions = format!("{:?}", receipts[0].actions);
+        assert!(actions.contains("FunctionCall") && actions.contains("pause"));
+
+        // The receipt runs with the contract account as the predecessor.
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.pause();
+        assert!(contract.is_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "More gas is required")]
+    fn test_execute_not_enough_gas() {
+        let (mut context, mut contract) = setup();
+        let proposal_id = contract.propose("pause".to_string(), "{}".to_string());
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .prepaid_gas(Gas(10_000_000_000_000))
+            .build());
+        contract.confirm(proposal_id);
+        contract.execute(proposal_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "The method on_ft_rescued can't be proposed")]
+    fn test_propose_private_callback() {
+        let (_, mut contract) = setup();
+        contract.propose("on_ft_rescued".to_string(), "{}".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The proposal 0 has 1 of 2 required confirmations")]
+    fn test_execute_under_threshold() {
+        let (_, mut contract) = setup();
+        let proposal_id = contract.propose("pause".to_string(), "{}".to_string());
+        contract.execute(proposal_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_direct_owner_call_with_multisig() {
+        let (_, mut contract) = setup();
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only a multisig owner can call this method")]
+    fn test_propose_not_multisig_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.propose("pause".to_string(), "{}".to_string());
+    }
+}
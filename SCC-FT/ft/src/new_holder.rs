@@ -0,0 +1,129 @@
+//! An optional contract notified through its `on_new_holder` method whenever an account's balance
+//! goes from zero to positive, e.g. to track the holders off the token contract. The notification
+//! is fire-and-forget: nothing waits for its result, so a failing or missing method never blocks
+//! or reverts the transfer or mint that created the holder.
+use crate::events::SettingUpdate;
+use crate::*;
+
+/// The gas attached to the `on_new_holder` call.
+const GAS_FOR_ON_NEW_HOLDER: Gas = Gas(5_000_000_000_000);
+
+#[ext_contract(ext_new_holder_listener)]
+pub trait NewHolderListener {
+    fn on_new_holder(&mut self, account_id: AccountId);
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the contract notified of new holders, if any.
+    pub fn get_on_new_holder_contract(&self) -> Option<AccountId> {
+        self.on_new_holder_contract.clone()
+    }
+
+    /// Sets the contract notified of new holders, or removes it with `None`. Can only be called
+    /// by the owner.
+    pub fn set_on_new_holder_contract(&mut self, contract_id: Option<AccountId>) {
+        self.assert_owner();
+        self.emit_admin_event(
+            "on_new_holder_contract_update",
+            SettingUpdate {
+                account_id: None,
+                old_value: self.on_new_holder_contract.clone(),
+                new_value: contract_id.clone(),
+            },
+        );
+        self.on_new_holder_contract = contract_id;
+    }
+}
+
+impl Contract {
+    /// Notifies the new holder contract, if any, that `account_id` became a holder.
+    pub(crate) fn internal_notify_new_holder(&self, account_id: &AccountId) {
+        if let Some(contract_id) = self.on_new_holder_contract.clone() {
+            ext_new_holder_listener::on_new_holder(
+                account_id.clone(),
+                contract_id,
+                0,
+                GAS_FOR_ON_NEW_HOLDER,
+            );
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_on_new_holder_contract(Some(accounts(5)));
+        (context, contract)
+    }
+
+    fn new_holder_calls() -> usize {
+        get_created_receipts()
+            .iter()
+            .filter(|receipt| receipt.receiver_id.as_str() == accounts(5).as_str())
+            .count()
+    }
+
+    #[test]
+    fn test_set_on_new_holder_contract() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.get_on_new_holder_contract(), Some(accounts(5)));
+        contract.set_on_new_holder_contract(None);
+        assert_eq!(contract.get_on_new_holder_contract(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_set_on_new_holder_contract_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_on_new_holder_contract(None);
+    }
+
+    #[test]
+    fn test_notified_on_first_receipt_only() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(new_holder_calls(), 1);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(new_holder_calls(), 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 20);
+    }
+
+    #[test]
+    fn test_notified_on_mint() {
+        let (_, mut contract) = setup();
+        contract.mint(accounts(1), 10.into(), None);
+        assert_eq!(new_holder_calls(), 1);
+    }
+
+    #[test]
+    fn test_not_notified_without_contract() {
+        let (mut context, mut contract) = setup();
+        contract.set_on_new_holder_contract(None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+        assert_eq!(get_created_receipts().len(), 0);
+    }
+}
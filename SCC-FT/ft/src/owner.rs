@@ -0,0 +1,206 @@
+use crate::*;
+use near_sdk::serde_json::json;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the account that controls the token.
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// Returns the account proposed as the next owner, if any.
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+
+    /// Proposes `new_owner` as the next owner. The ownership is transferred only once the
+    /// proposed account calls `accept_ownership`. Can only be called by the owner.
+    pub fn propose_new_owner(&mut self, new_owner: AccountId) {
+        self.assert_owner();
+        log!("Ownership transfer from @{} to @{} proposed", self.owner_id, new_owner);
+        self.pending_owner = Some(new_owner);
+    }
+
+    /// Completes the ownership transfer. Can only be called by the pending owner.
+    pub fn accept_ownership(&mut self) {
+        let account_id = env::predecessor_account_id();
+        assert_eq!(
+            self.pending_owner.as_ref(),
+            Some(&account_id),
+            "Only the pending owner can accept the ownership"
+        );
+        log!("Ownership transferred from @{} to @{}", self.owner_id, account_id);
+        self.owner_id = account_id;
+        self.pending_owner = None;
+    }
+
+    /// Aborts a proposed ownership transfer. Can only be called by the owner.
+    pub fn cancel_ownership_transfer(&mut self) {
+        self.assert_owner();
+        let pending_owner = self.pending_owner.take().expect("No pending ownership transfer");
+        log!("Ownership transfer to @{} cancelled", pending_owner);
+    }
+
+    /// Returns `true` if the ownership was renounced and the owner-only methods are disabled.
+    pub fn is_ownership_renounced(&self) -> bool {
+        self.ownership_renounced
+    }
+
+    /// Renounces the ownership permanently, leaving the contract account as the owner. All
+    /// owner-only methods, including the multisig proposals, panic afterwards, and the minters
+    /// lose their role. Can only be called by the owner.
+    pub fn renounce_ownership(&mut self) {
+        self.assert_owner();
+        let previous_owner_id = std::mem::replace(&mut self.owner_id, env::current_account_id());
+        self.ownership_renounced = true;
+        self.pending_owner = None;
+        self.multisig_owners = Vec::new();
+        self.multisig_threshold = 1;
+        self.minters.clear();
+        self.emit_event(
+            "ownership_renounced",
+            &[json!({ "previous_owner_id": previous_owner_id })],
+        );
+    }
+}
+
+impl Contract {
+    /// Panics unless the owner calls, or the contract itself when executing a multisig
+    /// proposal.
+    pub(crate) fn assert_owner(&self) {
+        scc_assert!(
+            self.is_owner_call(),
+            SccError::NotOwner,
+            "Only the owner can call this method"
+        );
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+
+        contract.propose_new_owner(accounts(2));
+        assert_eq!(contract.get_owner(), accounts(1));
+        assert_eq!(contract.get_pending_owner(), Some(accounts(2)));
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.accept_ownership();
+        assert_eq!(
+            get_logs(),
+            vec![format!("Ownership transferred from @{} to @{}", accounts(1), accounts(2))]
+        );
+        assert_eq!(contract.get_owner(), accounts(2));
+        assert_eq!(contract.get_pending_owner(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the pending owner can accept the ownership")]
+    fn test_accept_ownership_wrong_caller() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.propose_new_owner(accounts(2));
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.accept_ownership();
+    }
+
+    #[test]
+    fn test_cancel_ownership_transfer() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.propose_new_owner(accounts(2));
+        contract.cancel_ownership_transfer();
+        assert_eq!(contract.get_pending_owner(), None);
+        assert_eq!(contract.get_owner(), accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the pending owner can accept the ownership")]
+    fn test_accept_ownership_after_cancel() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.propose_new_owner(accounts(2));
+        contract.cancel_ownership_transfer();
+
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_propose_new_owner_not_owner() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.propose_new_owner(accounts(2));
+    }
+
+    #[test]
+    fn test_renounce_ownership() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.add_minter(accounts(3));
+        contract.renounce_ownership();
+        assert!(contract.is_ownership_renounced());
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert!(contract.get_minters().is_empty());
+        assert_eq!(
+            get_logs().last().unwrap(),
+            r#"EVENT_JSON:{"standard":"scc","version":"1.0.0","event":"ownership_renounced","data":[{"previous_owner_id":"bob"}]}"#
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_pause_after_renounce() {
+        let context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.renounce_ownership();
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner or a minter can call this method")]
+    fn test_mint_after_renounce() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(1), TOTAL_SUPPLY.into())
+        });
+        contract.renounce_ownership();
+        // Not even the contract account itself can use the owner-only methods anymore.
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.mint(accounts(1), 1.into(), None);
+    }
+}
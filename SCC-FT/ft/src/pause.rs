@@ -0,0 +1,134 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if transfers are currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses all transfers. Minting and burning by the owner keep working while paused.
+    /// Can only be called by the owner.
+    pub fn pause(&mut self) {
+        self.assert_owner();
+        assert!(!self.paused, "Transfers are already paused");
+        self.paused = true;
+        log!("Transfers paused by @{}", self.owner_id);
+    }
+
+    /// Resumes transfers. Can only be called by the owner.
+    pub fn unpause(&mut self) {
+        self.assert_owner();
+        assert!(self.paused, "Transfers are not paused");
+        assert!(
+            self.pending_redenomination.is_none(),
+            "Transfers can't resume before the redenomination is complete"
+        );
+        assert!(
+            self.pending_split.is_none(),
+            "Transfers can't resume before the split is complete"
+        );
+        assert!(
+            self.pending_snapshot.is_none(),
+            "Transfers can't resume before the snapshot is finalized"
+        );
+        self.paused = false;
+        log!("Transfers unpaused by @{}", self.owner_id);
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self) {
+        scc_assert!(!self.paused, SccError::Paused, "Transfers are paused");
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_pause_and_unpause_transfers() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.pause();
+        assert!(contract.is_paused());
+
+        // Minting keeps working while paused.
+        contract.mint(accounts(1), 10.into(), None);
+
+        contract.unpause();
+        assert!(!contract.is_paused());
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 15);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers are paused")]
+    fn test_transfer_while_paused() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.pause();
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers are paused")]
+    fn test_transfer_call_while_paused() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.pause();
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer_call(accounts(1), 5.into(), None, "".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_pause_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.pause();
+    }
+}
@@ -0,0 +1,299 @@
+//! Permit-style approvals: an account registers an ed25519 public key once, then anyone can
+//! relay an allowance signed with the matching secret key, so the account doesn't pay for gas.
+//!
+//! The signatures are verified with `ed25519-dalek`, since near-sdk 4.0.0-pre.7 doesn't expose
+//! the `ed25519_verify` host function.
+use crate::*;
+use ed25519_dalek::{PublicKey as Ed25519PublicKey, Signature, Verifier};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::PublicKey;
+use std::convert::TryFrom;
+
+/// The message signed for `approve_with_signature`. The signature is computed over the SHA-256
+/// hash of the Borsh-serialized message.
+#[derive(BorshSerialize)]
+pub(crate) struct PermitMessage {
+    pub contract_id: AccountId,
+    pub owner: AccountId,
+    pub spender: AccountId,
+    pub amount: Balance,
+    pub nonce: u64,
+    pub deadline: u64,
+}
+
+impl PermitMessage {
+    pub(crate) fn hash(&self) -> Vec<u8> {
+        env::sha256(&self.try_to_vec().unwrap())
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the nonce that the next permit of the given account has to be signed with.
+    pub fn get_permit_nonce(&self, owner: AccountId) -> u64 {
+        self.permit_nonces.get(&owner).unwrap_or(0)
+    }
+
+    /// Registers the ed25519 public key used to verify the permits of the registered caller.
+    /// Requires at least one yoctoNEAR attached, and enough to cover the storage of the key and
+    /// the nonce. The rest of the deposit is refunded.
+    #[payable]
+    pub fn set_permit_key(&mut self, public_key: PublicKey) {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        scc_assert!(
+            self.token.accounts.contains_key(&account_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            account_id
+        );
+        let bytes = public_key.as_bytes();
+        // The first byte is the curve type, 0 for ed25519.
+        assert!(bytes.len() == 33 && bytes[0] == 0, "Only ed25519 keys are supported");
+        self.permit_keys.insert(&account_id, &bytes[1..].to_vec());
+        // Stored up front, so that relaying a permit doesn't add it at the relayer's expense.
+        let nonce = self.get_permit_nonce(account_id.clone());
+        self.permit_nonces.insert(&account_id, &nonce);
+        self.internal_charge_storage(initial_storage_usage);
+    }
+
+    /// Sets the allowance of `spender` on the registered `owner`'s tokens to `amount`, authorized
+    /// by `owner`'s signature of the `PermitMessage` with `nonce`, which has to be the current
+    /// nonce of the owner. The permit expires once the block timestamp passes `deadline` (in
+    /// nanoseconds). Each signature can be used only once, since the nonce is incremented. The
+    /// storage of a new allowance is charged to the attached deposit and the rest of the deposit
+    /// is refunded.
+    #[payable]
+    pub fn approve_with_signature(
+        &mut self,
+        owner: AccountId,
+        spender: AccountId,
+        amount: U128,
+        nonce: u64,
+        deadline: U64,
+        signature: Base64VecU8,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        let deadline = deadline.0;
+        scc_assert!(
+            env::block_timestamp() <= deadline,
+            SccError::PermitExpired,
+            "The permit has expired"
+        );
+        scc_assert!(owner != spender, SccError::SelfApproval, "Can't approve to yourself");
+        let public_key = self
+            .permit_keys
+            .get(&owner)
+            .unwrap_or_else(|| SccError::NoPermitKey.panic("The owner has no permit key"));
+        let current_nonce = self.get_permit_nonce(owner.clone());
+        scc_assert!(
+            nonce >= current_nonce,
+            SccError::NonceUsed,
+            "The nonce {} was already used",
+            nonce
+        );
+        scc_assert!(
+            nonce == current_nonce,
+            SccError::InvalidNonce,
+            "The nonce should be {}",
+            current_nonce
+        );
+        let message = PermitMessage {
+            contract_id: env::current_account_id(),
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount: amount.0,
+            nonce,
+            deadline,
+        };
+        scc_assert!(
+            verify_signature(&public_key, &message.hash(), &signature.0),
+            SccError::InvalidSignature,
+            "Invalid signature"
+        );
+        self.permit_nonces.insert(&owner, &(nonce + 1));
+        self.internal_approve(&owner, &spender, amount.0, None);
+        self.internal_charge_storage(initial_storage_usage);
+        log!("@{} approved @{} to spend {} with a permit", owner, spender, amount.0);
+    }
+}
+
+fn verify_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let public_key = match Ed25519PublicKey::from_bytes(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    match Signature::try_from(signature) {
+        Ok(signature) => public_key.verify(message, &signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use ed25519_dalek::{Keypair, SecretKey, Signer};
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, STORAGE_DEPOSIT, TOTAL_SUPPLY};
+
+    const DEADLINE: u64 = 1_000;
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let public = (&secret).into();
+        Keypair { secret, public }
+    }
+
+    fn sign(nonce: u64) -> Base64VecU8 {
+        let message = PermitMessage {
+            contract_id: accounts(0),
+            owner: accounts(2),
+            spender: accounts(3),
+            amount: 100,
+            nonce,
+            deadline: DEADLINE,
+        };
+        Base64VecU8(keypair().sign(&message.hash()).to_bytes().to_vec())
+    }
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut public_key = vec![0];
+        public_key.extend_from_slice(keypair().public.as_bytes());
+        testing_env!(context.attached_deposit(STORAGE_DEPOSIT).build());
+        contract.set_permit_key(PublicKey::try_from(public_key).unwrap());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT)
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(DEADLINE)
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_approve_with_signature() {
+        let (_, mut contract) = setup();
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+        assert_eq!(contract.allowance(accounts(2), accounts(3)).0, 100);
+        assert_eq!(contract.get_permit_nonce(accounts(2)), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The permit has expired")]
+    fn test_approve_with_expired_signature() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(DEADLINE + 1).build());
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NONCE_USED: The nonce 0 was already used")]
+    fn test_approve_with_replayed_signature() {
+        let (_, mut contract) = setup();
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_NONCE: The nonce should be 0")]
+    fn test_approve_with_future_nonce() {
+        let (_, mut contract) = setup();
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            1,
+            DEADLINE.into(),
+            sign(1),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_DEPOSIT: The attached deposit should be at least")]
+    fn test_approve_with_signature_without_storage_deposit() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            100.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_REGISTERED: The account bob is not registered")]
+    fn test_set_permit_key_not_registered() {
+        let (mut context, mut contract) = setup();
+        let mut public_key = vec![0];
+        public_key.extend_from_slice(keypair().public.as_bytes());
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_permit_key(PublicKey::try_from(public_key).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INSUFFICIENT_DEPOSIT: The attached deposit should be at least")]
+    fn test_set_permit_key_without_storage_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let mut public_key = vec![0];
+        public_key.extend_from_slice(keypair().public.as_bytes());
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_permit_key(PublicKey::try_from(public_key).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SIGNATURE: Invalid signature")]
+    fn test_approve_with_tampered_amount() {
+        let (_, mut contract) = setup();
+        contract.approve_with_signature(
+            accounts(2),
+            accounts(3),
+            1_000.into(),
+            0,
+            DEADLINE.into(),
+            sign(0),
+        );
+    }
+}
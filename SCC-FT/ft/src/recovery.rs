@@ -0,0 +1,273 @@
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::is_promise_success;
+
+/// The gas attached to the `ft_transfer` call of the rescued token.
+const GAS_FOR_RESCUE_FT_TRANSFER: Gas = Gas(10_000_000_000_000);
+/// The gas attached to the `on_ft_rescued` callback.
+const GAS_FOR_ON_FT_RESCUED: Gas = Gas(5_000_000_000_000);
+
+#[ext_contract(ext_rescued_token)]
+trait RescuedToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_rescue_resolver)]
+trait RescueResolver {
+    fn on_ft_rescued(&mut self, token_contract: AccountId, to: AccountId, amount: U128) -> bool;
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers the tokens held by the contract account itself, e.g. refunds that landed on it,
+    /// to the registered account `to`. The tokens locked for vesting and the reflection pool are
+    /// never touched.
+    /// Returns the swept amount. Can only be called by the owner.
+    pub fn sweep_contract_balance(&mut self, to: AccountId) -> U128 {
+        self.assert_owner();
+        let contract_id = env::current_account_id();
+        let amount = self.internal_free_contract_balance();
+        scc_assert!(
+            amount > 0,
+            SccError::InsufficientBalance,
+            "The contract doesn't hold any tokens to sweep"
+        );
+        internal::assert_not_contract(&to);
+        self.internal_settle_reflection(&to);
+        self.internal_withdraw(&contract_id, amount);
+        self.internal_deposit(&to, amount);
+        FtTransfer {
+            old_owner_id: &contract_id,
+            new_owner_id: &to,
+            amount: &U128(amount),
+            memo: Some("Contract balance swept"),
+        }
+        .emit();
+        amount.into()
+    }
+
+    /// Transfers `amount` of the tokens held by the contract account itself, e.g. escrowed
+    /// tokens, to the registered `receiver_id`. Unlike `sweep_contract_balance`, the rest stays
+    /// on the contract account. The tokens locked for vesting and the reflection pool can't be
+    /// released. Can only be called by the owner.
+    pub fn release_from_contract(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        self.assert_owner();
+        let contract_id = env::current_account_id();
+        internal::assert_not_contract(&receiver_id);
+        scc_assert!(
+            self.token.accounts.contains_key(&receiver_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            receiver_id
+        );
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        scc_assert!(
+            amount.0 <= self.internal_free_contract_balance(),
+            SccError::InsufficientBalance,
+            "The contract doesn't hold enough tokens to release"
+        );
+        self.internal_settle_reflection(&receiver_id);
+        self.internal_withdraw(&contract_id, amount.0);
+        self.internal_deposit(&receiver_id, amount.0);
+        FtTransfer {
+            old_owner_id: &contract_id,
+            new_owner_id: &receiver_id,
+            amount: &amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Recovers `amount` tokens of another NEP-141 contract `token_contract` that were sent to
+    /// this contract account by mistake, by transferring them to `to`. The tokens of this
+    /// contract can't be rescued this way. The outcome is logged by the `on_ft_rescued`
+    /// callback. Can only be called by the owner.
+    pub fn rescue_ft(&mut self, token_contract: AccountId, to: AccountId, amount: U128) -> Promise {
+        self.assert_owner();
+        assert_ne!(
+            token_contract,
+            env::current_account_id(),
+            "The tokens of this contract can't be rescued"
+        );
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        ext_rescued_token::ft_transfer(
+            to.clone(),
+            amount,
+            Some("Rescued tokens".to_string()),
+            token_contract.clone(),
+            // `ft_transfer` requires exactly one yoctoNEAR attached.
+            1,
+            GAS_FOR_RESCUE_FT_TRANSFER,
+        )
+        .then(ext_rescue_resolver::on_ft_rescued(
+            token_contract,
+            to,
+            amount,
+            env::current_account_id(),
+            core_impl::NO_DEPOSIT,
+            GAS_FOR_ON_FT_RESCUED,
+        ))
+    }
+
+    /// Logs whether the `ft_transfer` of `rescue_ft` succeeded and returns `true` if it did.
+    #[private]
+    pub fn on_ft_rescued(
+        &mut self,
+        token_contract: AccountId,
+        to: AccountId,
+        amount: U128,
+    ) -> bool {
+        let rescued = is_promise_success();
+        if rescued {
+            log!("Rescued {} tokens of @{} to @{}", amount.0, token_contract, to);
+        } else {
+            log!("Failed to rescue {} tokens of @{} to @{}", amount.0, token_contract, to);
+        }
+        rescued
+    }
+}
+
+impl Contract {
+    /// Returns the tokens held by the contract account that are neither locked for vesting nor
+    /// part of the reflection pool.
+    fn internal_free_contract_balance(&self) -> Balance {
+        let balance = self.token.accounts.get(&env::current_account_id()).unwrap_or(0);
+        balance - self.vesting_escrow_balance - self.reflection_pool
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PromiseResult, RuntimeFeesConfig, VMConfig};
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_sweep_contract_balance() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.create_vesting(
+            accounts(1),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: 10,
+                end_timestamp: 20,
+                total: U128(1_000),
+                claimed: U128(0),
+            },
+        );
+        // Simulates a refund that landed on the contract account.
+        contract.token.internal_deposit(&accounts(0), 50);
+
+        assert_eq!(contract.sweep_contract_balance(accounts(1)).0, 50);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 50);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 1_000);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 50);
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract doesn't hold any tokens to sweep")]
+    fn test_sweep_empty_contract_balance() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.sweep_contract_balance(accounts(2));
+    }
+
+    /// Deposits 100 tokens to the contract account, simulating an escrow deposit.
+    fn setup_escrow() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.token.internal_deposit(&accounts(0), 100);
+        (context, contract)
+    }
+
+    #[test]
+    fn test_release_from_contract() {
+        let (mut context, mut contract) = setup_escrow();
+        testing_env!(context.build());
+        contract.release_from_contract(accounts(1), 30.into(), Some("Escrow release".to_string()));
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 30);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 70);
+        assert_eq!(
+            get_logs(),
+            vec![
+                r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","amount":"30","memo":"Escrow release"}]}"#
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The contract doesn't hold enough tokens to release")]
+    fn test_release_more_than_contract_balance() {
+        let (_, mut contract) = setup_escrow();
+        contract.release_from_contract(accounts(1), 101.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account danny is not registered")]
+    fn test_release_to_unregistered_account() {
+        let (_, mut contract) = setup_escrow();
+        contract.release_from_contract(accounts(3), 10.into(), None);
+    }
+
+    #[test]
+    fn test_rescue_ft() {
+        let (mut context, mut contract) = setup_escrow();
+        contract.rescue_ft(accounts(4), accounts(1), 500.into());
+        // The tokens of this contract are not touched.
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 100);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+
+        for (result, log) in [
+            (PromiseResult::Successful(vec![]), "Rescued 500 tokens of @eugene to @bob"),
+            (PromiseResult::Failed, "Failed to rescue 500 tokens of @eugene to @bob"),
+        ] {
+            let rescued = matches!(result, PromiseResult::Successful(_));
+            testing_env!(
+                context.predecessor_account_id(accounts(0)).build(),
+                VMConfig::test(),
+                RuntimeFeesConfig::test(),
+                Default::default(),
+                vec![result]
+            );
+            assert_eq!(contract.on_ft_rescued(accounts(4), accounts(1), 500.into()), rescued);
+            assert_eq!(get_logs(), vec![log]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The tokens of this contract can't be rescued")]
+    fn test_rescue_own_token() {
+        let (_, mut contract) = setup_escrow();
+        contract.rescue_ft(accounts(0), accounts(1), 50.into());
+    }
+}
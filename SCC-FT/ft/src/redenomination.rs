@@ -0,0 +1,241 @@
+use crate::*;
+
+/// The maximum number of registered accounts a single `redenominate` call can scale within the
+/// gas limit.
+const MAX_REDENOMINATION_ACCOUNTS: u64 = 250;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the decimals, the multiplier and the index of the next account to scale of the
+    /// redenomination in progress, if any.
+    pub fn get_pending_redenomination(&self) -> Option<(u8, U128, u64)> {
+        self.pending_redenomination.map(|(new_decimals, multiplier, next_index)| {
+            (new_decimals, U128(multiplier), next_index)
+        })
+    }
+
+    /// Redenominates the token, multiplying every balance by `multiplier` and changing the
+    /// decimals of the metadata to `new_decimals`, which have to be more than the current ones
+    /// and at most 24. The multiplier has to be 10 to the power of the added decimals. The first
+    /// call, with `from_index` 0, scales the total supply, the max supply and the tokens held by
+    /// the contract for vesting and reflections, and updates the metadata. Every call then scales
+    /// the balances and vesting schedules of at most `limit` registered accounts starting at
+    /// `from_index`, continuing where the previous call ended, and returns the index of the next
+    /// account to scale. The redenomination is complete once that index reaches the number of
+    /// registered accounts.
+    ///
+    /// Transfers have to stay paused and accounts can't be unregistered until the
+    /// redenomination is complete. Settings and allowances given in token units, e.g. the
+    /// minimum transfer amount, are not scaled. Accounts registered before the migration of the
+    /// initial release are not listed, so their balances are not scaled either. Can only be
+    /// called by the owner.
+    pub fn redenominate(
+        &mut self,
+        new_decimals: u8,
+        multiplier: U128,
+        from_index: u64,
+        limit: u64,
+    ) -> u64 {
+        self.assert_owner();
+        assert!(self.paused, "Transfers should be paused during a redenomination");
+        assert!(self.pending_split.is_none(), "A split is in progress");
+        let next_index = match self.pending_redenomination {
+            Some((pending_decimals, pending_multiplier, next_index)) => {
+                assert!(
+                    pending_decimals == new_decimals && pending_multiplier == multiplier.0,
+                    "Another redenomination is in progress"
+                );
+                next_index
+            }
+            None => {
+                self.internal_start_redenomination(new_decimals, multiplier.0);
+                0
+            }
+        };
+        assert_eq!(from_index, next_index, "The redenomination continues at index {}", next_index);
+        let accounts_count = self.registered_accounts.len();
+        let to_index = std::cmp::min(
+            from_index + std::cmp::min(limit, MAX_REDENOMINATION_ACCOUNTS),
+            accounts_count,
+        );
+        let accounts = self.registered_accounts.as_vector();
+        for index in from_index..to_index {
+            let account_id = accounts.get(index).unwrap();
+            // Every balance fits, since the scaled total supply does. The pending reflection
+            // rewards scale with the balances and the reflection pool.
+            let balance = self.token.accounts.get(&account_id).unwrap_or(0);
+            self.token.accounts.insert(&account_id, &(balance * multiplier.0));
+            if let Some(mut grants) = self.vesting.get(&account_id) {
+                for grant_index in 0..grants.len() {
+                    let mut schedule = grants.get(grant_index).unwrap();
+                    schedule.total = U128(schedule.total.0 * multiplier.0);
+                    schedule.claimed = U128(schedule.claimed.0 * multiplier.0);
+                    grants.replace(grant_index, &schedule);
+                }
+            }
+        }
+        if to_index == accounts_count {
+            self.pending_redenomination = None;
+            log!("Redenominated to {} decimals", new_decimals);
+        } else {
+            self.pending_redenomination = Some((new_decimals, multiplier.0, to_index));
+        }
+        to_index
+    }
+}
+
+impl Contract {
+    /// Scales the contract-wide amounts by `multiplier` and sets the decimals of the metadata,
+    /// panicking if any amount would overflow. The multiplier has to match the added decimals,
+    /// so the value of a whole token, and the NEAR backing wrapped NEAR, stay the same.
+    fn internal_start_redenomination(&mut self, new_decimals: u8, multiplier: Balance) {
+        metadata::assert_valid_decimals(new_decimals);
+        let old_decimals = self.ft_decimals();
+        assert!(
+            new_decimals > old_decimals,
+            "The new decimals should be more than the current {}",
+            old_decimals
+        );
+        assert_eq!(
+            multiplier,
+            10u128.pow((new_decimals - old_decimals) as u32),
+            "The multiplier should be 10^{} to go from {} to {} decimals",
+            new_decimals - old_decimals,
+            old_decimals,
+            new_decimals
+        );
+        let scale = |amount: Balance| {
+            amount
+                .checked_mul(multiplier)
+                .unwrap_or_else(|| env::panic_str("The redenomination would overflow the supply"))
+        };
+        self.token.total_supply = scale(self.token.total_supply);
+        self.max_supply = self.max_supply.map(scale);
+        self.total_burned = scale(self.total_burned);
+        self.reflection_pool = scale(self.reflection_pool);
+        self.vesting_escrow_balance = scale(self.vesting_escrow_balance);
+        let mut metadata = self.metadata.get().unwrap();
+        metadata.decimals = new_decimals;
+        self.metadata.set(&metadata);
+        log!(
+            "Redenomination to {} decimals started with a multiplier of {}",
+            new_decimals,
+            multiplier
+        );
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        contract.ft_transfer(accounts(3), 250.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.pause();
+        (context, contract)
+    }
+
+    #[test]
+    fn test_redenominate_in_chunks() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.redenominate(10, 100.into(), 0, 2), 2);
+        assert_eq!(contract.get_pending_redenomination(), Some((10, U128(100), 2)));
+        assert_eq!(contract.ft_metadata().decimals, 10);
+        assert_eq!(contract.redenominate(10, 100.into(), 2, 2), 3);
+        assert_eq!(contract.get_pending_redenomination(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10_000);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 25_000);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, (TOTAL_SUPPLY - 350) * 100);
+        let total_balance: Balance = [accounts(1), accounts(2), accounts(3)]
+            .iter()
+            .map(|account_id| contract.ft_balance_of(account_id.clone()).0)
+            .sum();
+        assert_eq!(contract.ft_total_supply().0, total_balance);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY * 100);
+    }
+
+    #[test]
+    #[should_panic(expected = "The redenomination continues at index 2")]
+    fn test_redenominate_skipped_chunk() {
+        let (_, mut contract) = setup();
+        contract.redenominate(10, 100.into(), 0, 2);
+        contract.redenominate(10, 100.into(), 3, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Accounts can't be unregistered during a redenomination")]
+    fn test_force_unregister_during_redenomination() {
+        let (_, mut contract) = setup();
+        contract.redenominate(10, 100.into(), 0, 2);
+        contract.force_unregister(accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Accounts can't be unregistered during a redenomination")]
+    fn test_storage_unregister_during_redenomination() {
+        let (mut context, mut contract) = setup();
+        contract.redenominate(10, 100.into(), 0, 2);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.storage_unregister(Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "The redenomination would overflow the supply")]
+    fn test_redenominate_overflow() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), U128(u128::MAX / 10 + 1))
+        });
+        contract.pause();
+        contract.redenominate(9, 10.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The decimals can't exceed 24")]
+    fn test_redenominate_too_many_decimals() {
+        let (_, mut contract) = setup();
+        contract.redenominate(25, U128(10u128.pow(17)), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The multiplier should be 10^2 to go from 8 to 10 decimals")]
+    fn test_redenominate_wrong_multiplier() {
+        let (_, mut contract) = setup();
+        contract.redenominate(10, 1_000.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The new decimals should be more than the current 8")]
+    fn test_redenominate_fewer_decimals() {
+        let (_, mut contract) = setup();
+        contract.redenominate(6, 1.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers should be paused during a redenomination")]
+    fn test_redenominate_not_paused() {
+        let (_, mut contract) = setup();
+        contract.unpause();
+        contract.redenominate(10, 100.into(), 0, 10);
+    }
+}
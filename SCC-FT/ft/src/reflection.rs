@@ -0,0 +1,193 @@
+//! Reflection rewards: a share of every transfer is skimmed into a pool held by the contract
+//! account and distributed to all holders in proportion to their balances.
+//!
+//! Instead of touching every account on each transfer, the contract keeps a global
+//! `reflection_index`, the cumulative reward per token scaled by `REFLECTION_PRECISION`. Every
+//! account stores the index at which it was last settled, and its pending reward is
+//! `balance * (reflection_index - checkpoint) / REFLECTION_PRECISION` rounded down. Pending
+//! rewards are moved from the pool into the stored balance before any change of the balance,
+//! and `ft_balance_of` reports the stored balance plus the pending reward.
+//!
+//! Rounding always favors the pool: the index increase of every fee is rounded down and so is
+//! every pending reward, so the paid rewards never exceed the pool. The rounding dust, at most
+//! one token per account and per settlement, stays in the pool. The contract account, which
+//! holds the pool and the vesting escrow, doesn't earn rewards.
+use crate::events::SettingUpdate;
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+
+/// The scale of `reflection_index`.
+const REFLECTION_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// The maximum reflection fee in basis points (10%).
+const MAX_REFLECTION_FEE_BPS: u16 = 1_000;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the share of every transfer distributed to the holders in basis points.
+    pub fn get_reflection_fee(&self) -> u16 {
+        self.reflection_fee_bps
+    }
+
+    /// Returns the amount of tokens in the reflection pool that wasn't paid out to the holders
+    /// yet, including their pending rewards.
+    pub fn get_reflection_pool(&self) -> U128 {
+        self.reflection_pool.into()
+    }
+
+    /// Sets the share of every transfer distributed to the holders in basis points.
+    /// Can only be called by the owner.
+    pub fn set_reflection_fee(&mut self, bps: u16) {
+        self.assert_owner();
+        assert!(
+            bps <= MAX_REFLECTION_FEE_BPS,
+            "The reflection fee can't exceed {} basis points",
+            MAX_REFLECTION_FEE_BPS
+        );
+        self.emit_admin_event(
+            "reflection_fee_update",
+            SettingUpdate { account_id: None, old_value: self.reflection_fee_bps, new_value: bps },
+        );
+        self.reflection_fee_bps = bps;
+    }
+}
+
+impl Contract {
+    /// Returns the balance of the given account including its pending reflection reward.
+    pub(crate) fn internal_balance_of(&self, account_id: &AccountId) -> Balance {
+        let balance = self.token.accounts.get(account_id).unwrap_or(0);
+        balance + self.internal_pending_reflection(account_id, balance)
+    }
+
+    /// Moves the pending reflection reward of the given account from the pool into its balance.
+    /// Must be called before any change of the account balance.
+    pub(crate) fn internal_settle_reflection(&mut self, account_id: &AccountId) {
+        if self.reflection_index == 0 || account_id == &env::current_account_id() {
+            return;
+        }
+        let balance = match self.token.accounts.get(account_id) {
+            Some(balance) => balance,
+            None => return,
+        };
+        let pending = self.internal_pending_reflection(account_id, balance);
+        if pending > 0 {
+            let contract_id = env::current_account_id();
+            self.internal_withdraw(&contract_id, pending);
+            self.internal_deposit(account_id, pending);
+            self.reflection_pool -= pending;
+        }
+        self.reflection_checkpoints.insert(account_id, &self.reflection_index);
+    }
+
+    /// Skims `amount` tokens of `sender_id` into the reflection pool and distributes them over
+    /// the eligible supply by increasing the reflection index.
+    pub(crate) fn internal_reflect(&mut self, sender_id: &AccountId, amount: Balance) {
+        let contract_id = env::current_account_id();
+        if !self.token.accounts.contains_key(&contract_id) {
+            self.internal_register_account(&contract_id);
+        }
+        self.internal_settle_reflection(sender_id);
+        self.internal_withdraw(sender_id, amount);
+        self.internal_deposit(&contract_id, amount);
+        self.reflection_pool += amount;
+        let eligible_supply =
+            self.token.total_supply - self.token.accounts.get(&contract_id).unwrap_or(0);
+        if eligible_supply > 0 {
+            self.reflection_index += amount * REFLECTION_PRECISION / eligible_supply;
+        }
+        FtTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: &contract_id,
+            amount: &U128(amount),
+            memo: Some("Reflection fee"),
+        }
+        .emit();
+    }
+
+    fn internal_pending_reflection(&self, account_id: &AccountId, balance: Balance) -> Balance {
+        if balance == 0 || account_id == &env::current_account_id() {
+            return 0;
+        }
+        let checkpoint = self.reflection_checkpoints.get(account_id).unwrap_or(0);
+        let index_delta = self.reflection_index - checkpoint;
+        balance / REFLECTION_PRECISION * index_delta
+            + balance % REFLECTION_PRECISION * index_delta / REFLECTION_PRECISION
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account};
+
+    /// Starts with 5_000 tokens for the owner, 3_000 for bob and 2_000 for danny, and a 10%
+    /// reflection fee.
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract =
+            init_contract(&context, || Contract::new_default_meta(accounts(2), 10_000.into()));
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 3_000.into(), None);
+        contract.ft_transfer(accounts(3), 2_000.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_reflection_fee(1_000);
+        testing_env!(context.attached_deposit(1).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_reflection_distributes_proportionally() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 1_000.into(), None);
+
+        // The fee of 100 is shared over the eligible supply of 9_900 at the time it's skimmed:
+        // the owner with 5_000, bob with 2_900 and danny with 2_000. The pool keeps the owner's
+        // unsettled reward of 50 and the rounding dust of 1.
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 5_050);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 2_029);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 2_920);
+        assert_eq!(contract.get_reflection_pool().0, 51);
+
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        contract.ft_transfer(accounts(1), 929.into(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 5_097);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 2_885);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 2_017);
+        assert_eq!(contract.get_reflection_pool().0, 98);
+        assert_eq!(contract.ft_total_supply().0, 10_000);
+    }
+
+    #[test]
+    fn test_reflected_balance_can_be_transferred() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 1_000.into(), None);
+
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(2)).build());
+        contract.set_reflection_fee(0);
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 5_050.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 7_079);
+        assert_eq!(contract.get_reflection_pool().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reflection fee can't exceed 1000 basis points")]
+    fn test_reflection_fee_above_max() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_reflection_fee(1_001);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::*;
+
+/// The maximum number of registered accounts `snapshot` and `snapshot_chunk` can record within
+/// the gas limit.
+const MAX_SNAPSHOT_ACCOUNTS: u64 = 250;
+
+#[near_bindgen]
+impl Contract {
+    /// Records the balances of all registered accounts and returns the id of the snapshot.
+    /// Only works while there are at most 250 registered accounts, to stay within the gas limit;
+    /// larger holder sets are recorded with `snapshot_chunk`. Can only be called by the owner.
+    pub fn snapshot(&mut self) -> u64 {
+        self.assert_owner();
+        let accounts_count = self.registered_accounts.len();
+        assert!(
+            accounts_count <= MAX_SNAPSHOT_ACCOUNTS,
+            "Too many accounts to snapshot in a single call"
+        );
+        let snapshot_id = self.snapshot_count;
+        self.snapshot_count += 1;
+        self.internal_record_snapshot_balances(snapshot_id, 0, accounts_count);
+        log!("Snapshot {} created", snapshot_id);
+        snapshot_id
+    }
+
+    /// Records the balances of at most `limit` registered accounts starting at `from_index` in
+    /// the snapshot `snapshot_id` and returns the index of the next account to record. A new
+    /// snapshot is started with the next snapshot id and `from_index` 0, and each chunk has to
+    /// continue where the previous one ended. Only one chunked snapshot can be in progress.
+    ///
+    /// Transfers have to stay paused and accounts can't be unregistered until the snapshot is
+    /// finalized, so that no tokens move between accounts recorded in different chunks. Can
+    /// only be called by the owner.
+    pub fn snapshot_chunk(&mut self, snapshot_id: u64, from_index: u64, limit: u64) -> u64 {
+        self.assert_owner();
+        assert!(self.paused, "Transfers should be paused during a chunked snapshot");
+        let next_index = match self.pending_snapshot {
+            Some((pending_id, next_index)) if pending_id == snapshot_id => next_index,
+            None if snapshot_id == self.snapshot_count => {
+                self.snapshot_count += 1;
+                log!("Snapshot {} started", snapshot_id);
+                0
+            }
+            _ => env::panic_str(&format!("The snapshot {} can't be recorded", snapshot_id)),
+        };
+        assert_eq!(
+            from_index, next_index,
+            "The snapshot {} continues at index {}",
+            snapshot_id, next_index
+        );
+        let to_index = std::cmp::min(
+            from_index + std::cmp::min(limit, MAX_SNAPSHOT_ACCOUNTS),
+            self.registered_accounts.len(),
+        );
+        self.internal_record_snapshot_balances(snapshot_id, from_index, to_index);
+        self.pending_snapshot = Some((snapshot_id, to_index));
+        to_index
+    }
+
+    /// Completes the chunked snapshot `snapshot_id` once all registered accounts are recorded,
+    /// making it immutable and readable with `balance_of_at`. Can only be called by the owner.
+    pub fn finalize_snapshot(&mut self, snapshot_id: u64) {
+        self.assert_owner();
+        match self.pending_snapshot {
+            Some((pending_id, next_index)) if pending_id == snapshot_id => assert!(
+                next_index >= self.registered_accounts.len(),
+                "The snapshot {} is not complete",
+                snapshot_id
+            ),
+            _ => env::panic_str(&format!("The snapshot {} is not in progress", snapshot_id)),
+        }
+        self.pending_snapshot = None;
+        log!("Snapshot {} created", snapshot_id);
+    }
+
+    /// Returns the balance of the given account at the time of the given finalized snapshot.
+    pub fn balance_of_at(&self, account_id: AccountId, snapshot_id: u64) -> U128 {
+        assert!(snapshot_id < self.snapshot_count, "The snapshot {} doesn't exist", snapshot_id);
+        assert!(
+            self.pending_snapshot.map_or(true, |(pending_id, _)| pending_id != snapshot_id),
+            "The snapshot {} is not finalized",
+            snapshot_id
+        );
+        self.snapshot_balances.get(&(snapshot_id, account_id)).unwrap_or(0).into()
+    }
+}
+
+impl Contract {
+    /// Records the non-zero balances of the registered accounts from `from_index` up to
+    /// `to_index` excluded in the given snapshot.
+    fn internal_record_snapshot_balances(
+        &mut self,
+        snapshot_id: u64,
+        from_index: u64,
+        to_index: u64,
+    ) {
+        let accounts = self.registered_accounts.as_vector();
+        for index in from_index..to_index {
+            let account_id = accounts.get(index).unwrap();
+            let balance = self.internal_balance_of(&account_id);
+            if balance > 0 {
+                self.snapshot_balances.insert(&(snapshot_id, account_id), &balance);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::accounts;
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_snapshot() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+
+        let snapshot_id = contract.snapshot();
+        assert_eq!(snapshot_id, 0);
+        contract.ft_transfer(accounts(1), 50.into(), None);
+
+        assert_eq!(contract.balance_of_at(accounts(1), snapshot_id).0, 100);
+        assert_eq!(contract.balance_of_at(accounts(2), snapshot_id).0, TOTAL_SUPPLY - 100);
+        assert_eq!(contract.balance_of_at(accounts(3), snapshot_id).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 150);
+
+        assert_eq!(contract.snapshot(), 1);
+        assert_eq!(contract.balance_of_at(accounts(1), 1).0, 150);
+    }
+
+    #[test]
+    #[should_panic(expected = "The snapshot 0 doesn't exist")]
+    fn test_missing_snapshot() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.balance_of_at(accounts(2), 0);
+    }
+
+    #[test]
+    fn test_snapshot_in_chunks() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        contract.ft_transfer(accounts(3), 200.into(), None);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.pause();
+        assert_eq!(contract.snapshot_chunk(0, 0, 2), 2);
+        assert_eq!(contract.snapshot_chunk(0, 2, 2), 3);
+        contract.finalize_snapshot(0);
+        contract.unpause();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(3), 50.into(), None);
+
+        assert_eq!(contract.balance_of_at(accounts(2), 0).0, TOTAL_SUPPLY - 300);
+        assert_eq!(contract.balance_of_at(accounts(1), 0).0, 100);
+        assert_eq!(contract.balance_of_at(accounts(3), 0).0, 200);
+        assert_eq!(contract.snapshot(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The snapshot 0 is not finalized")]
+    fn test_read_pending_snapshot() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.pause();
+        contract.snapshot_chunk(0, 0, 10);
+        contract.balance_of_at(accounts(2), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The snapshot 0 is not complete")]
+    fn test_finalize_incomplete_snapshot() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.pause();
+        assert_eq!(contract.snapshot_chunk(0, 0, 1), 1);
+        contract.finalize_snapshot(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The snapshot 0 can't be recorded")]
+    fn test_extend_finalized_snapshot() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.pause();
+        contract.snapshot_chunk(0, 0, 10);
+        contract.finalize_snapshot(0);
+        contract.snapshot_chunk(0, 1, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers should be paused during a chunked snapshot")]
+    fn test_snapshot_chunk_not_paused() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.snapshot_chunk(0, 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers can't resume before the snapshot is finalized")]
+    fn test_unpause_during_snapshot() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.pause();
+        contract.snapshot_chunk(0, 0, 10);
+        contract.unpause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Accounts can't be unregistered during a snapshot")]
+    fn test_force_unregister_during_snapshot() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.pause();
+        contract.snapshot_chunk(0, 0, 1);
+        contract.force_unregister(accounts(1));
+    }
+}
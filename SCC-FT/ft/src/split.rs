@@ -0,0 +1,322 @@
+//! Splits and reverse splits, multiplying every balance and the total supply by a ratio while
+//! the decimals stay the same, e.g. 2:1 doubles and 1:2 halves every balance.
+//!
+//! Dust policy: the scaled balances are rounded down, so a ratio that doesn't divide a balance
+//! drops the fraction of a token of that account. The dropped fractions are burned: once the
+//! split is complete, the difference between the scaled total supply and the sum of the scaled
+//! balances is added to the burned tokens, so the total supply keeps matching the balances.
+use crate::*;
+
+/// The maximum number of registered accounts a single `split` call can scale within the gas
+/// limit.
+const MAX_SPLIT_ACCOUNTS: u64 = 250;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the ratio numerator, the ratio denominator and the index of the next account to
+    /// scale of the split in progress, if any.
+    pub fn get_pending_split(&self) -> Option<(U128, U128, u64)> {
+        self.pending_split.map(|(numerator, denominator, next_index, _)| {
+            (U128(numerator), U128(denominator), next_index)
+        })
+    }
+
+    /// Splits the token, multiplying every balance and the total supply by
+    /// `ratio_numerator / ratio_denominator`, rounding down as described by the dust policy of
+    /// the module. The first call, with `from_index` 0, scales the max supply and the burned
+    /// tokens. Every call then settles the pending reflection rewards of at most `limit`
+    /// registered accounts starting at `from_index` and scales their balances and vesting
+    /// schedules, continuing where the previous call ended, and returns the index of the next
+    /// account to scale. The last call scales the tokens held by the contract for vesting and
+    /// reflections and burns the dust. The split is complete once the returned index reaches the
+    /// number of registered accounts.
+    ///
+    /// Transfers have to stay paused and accounts can't be unregistered until the split is
+    /// complete, and wrapped NEAR can't be split. Settings and allowances given in token units,
+    /// e.g. the minimum transfer amount, are not scaled. Accounts registered before the
+    /// migration of the initial release are not listed, so their balances are not scaled
+    /// either. Can only be called by the owner.
+    pub fn split(
+        &mut self,
+        ratio_numerator: U128,
+        ratio_denominator: U128,
+        from_index: u64,
+        limit: u64,
+    ) -> u64 {
+        self.assert_owner();
+        // The NEAR backing a wrapped token can't be split with it.
+        assert!(!self.wrapped, "Wrapped NEAR can't be split");
+        assert!(self.paused, "Transfers should be paused during a split");
+        assert!(self.pending_redenomination.is_none(), "A redenomination is in progress");
+        let (numerator, denominator) = (ratio_numerator.0, ratio_denominator.0);
+        let (next_index, target_supply) = match self.pending_split {
+            Some((pending_numerator, pending_denominator, next_index, target_supply)) => {
+                assert!(
+                    pending_numerator == numerator && pending_denominator == denominator,
+                    "Another split is in progress"
+                );
+                (next_index, target_supply)
+            }
+            None => (0, self.internal_start_split(numerator, denominator)),
+        };
+        assert_eq!(from_index, next_index, "The split continues at index {}", next_index);
+        let contract_id = env::current_account_id();
+        let accounts_count = self.registered_accounts.len();
+        let to_index =
+            std::cmp::min(from_index + std::cmp::min(limit, MAX_SPLIT_ACCOUNTS), accounts_count);
+        let accounts = self.registered_accounts.as_vector();
+        for index in from_index..to_index {
+            let account_id = accounts.get(index).unwrap();
+            // The contract account holds the tokens of the other accounts' rewards, it is scaled
+            // once all of them are settled.
+            if account_id == contract_id {
+                continue;
+            }
+            self.internal_settle_reflection(&account_id);
+            self.internal_split_balance(&account_id, numerator, denominator);
+            if let Some(mut grants) = self.vesting.get(&account_id) {
+                for grant_index in 0..grants.len() {
+                    let mut schedule = grants.get(grant_index).unwrap();
+                    // Rounding the claimed tokens up keeps the unclaimed tokens of all the
+                    // schedules within the scaled escrow.
+                    let total = split_amount(schedule.total.0, numerator, denominator);
+                    let claimed = split_amount(schedule.claimed.0, numerator, 1);
+                    let claimed = claimed / denominator + u128::from(claimed % denominator > 0);
+                    schedule.total = U128(total);
+                    schedule.claimed = U128(std::cmp::min(claimed, total));
+                    grants.replace(grant_index, &schedule);
+                }
+            }
+        }
+        if to_index == accounts_count {
+            self.internal_complete_split(numerator, denominator, target_supply);
+        } else {
+            self.pending_split = Some((numerator, denominator, to_index, target_supply));
+        }
+        to_index
+    }
+}
+
+impl Contract {
+    /// Checks the ratio and scales the max supply and the burned tokens, panicking if any amount
+    /// would overflow. Returns the scaled total supply the balances should add up to.
+    fn internal_start_split(&mut self, numerator: Balance, denominator: Balance) -> Balance {
+        assert!(numerator > 0, "The ratio numerator should be a positive number");
+        assert!(denominator > 0, "The ratio denominator should be a positive number");
+        assert_ne!(numerator, denominator, "The ratio should not be 1:1");
+        // No balance is larger than the total supply, so none of them can overflow either.
+        let target_supply = split_amount(self.token.total_supply, numerator, denominator);
+        self.max_supply =
+            self.max_supply.map(|max_supply| split_amount(max_supply, numerator, denominator));
+        self.total_burned = split_amount(self.total_burned, numerator, denominator);
+        log!("Split with a ratio of {}:{} started", numerator, denominator);
+        target_supply
+    }
+
+    /// Scales the tokens held by the contract and burns the dust of the rounded balances.
+    fn internal_complete_split(
+        &mut self,
+        numerator: Balance,
+        denominator: Balance,
+        target_supply: Balance,
+    ) {
+        let contract_id = env::current_account_id();
+        if self.token.accounts.contains_key(&contract_id) {
+            // The scaled contract balance is at least the scaled escrow and pool together.
+            self.internal_split_balance(&contract_id, numerator, denominator);
+        }
+        self.vesting_escrow_balance =
+            split_amount(self.vesting_escrow_balance, numerator, denominator);
+        self.reflection_pool = split_amount(self.reflection_pool, numerator, denominator);
+        let dust = target_supply.saturating_sub(self.token.total_supply);
+        self.total_burned += dust;
+        self.pending_split = None;
+        log!(
+            "Split with a ratio of {}:{} complete, {} tokens of dust burned",
+            numerator,
+            denominator,
+            dust
+        );
+    }
+
+    /// Scales the balance of `account_id`, updating the total supply by the same amount.
+    fn internal_split_balance(
+        &mut self,
+        account_id: &AccountId,
+        numerator: Balance,
+        denominator: Balance,
+    ) {
+        let balance = self.token.accounts.get(account_id).unwrap_or(0);
+        let new_balance = split_amount(balance, numerator, denominator);
+        self.token.accounts.insert(account_id, &new_balance);
+        self.token.total_supply = self.token.total_supply - balance + new_balance;
+        self.internal_update_holders(balance, new_balance);
+    }
+}
+
+/// Returns `amount * numerator / denominator` rounded down, panicking on overflow.
+fn split_amount(amount: Balance, numerator: Balance, denominator: Balance) -> Balance {
+    amount
+        .checked_mul(numerator)
+        .unwrap_or_else(|| env::panic_str("The split would overflow the supply"))
+        / denominator
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, test_metadata, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 101.into(), None);
+        contract.ft_transfer(accounts(3), 250.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.pause();
+        (context, contract)
+    }
+
+    fn total_balance(contract: &Contract) -> Balance {
+        [accounts(1), accounts(2), accounts(3)]
+            .iter()
+            .map(|account_id| contract.ft_balance_of(account_id.clone()).0)
+            .sum()
+    }
+
+    #[test]
+    fn test_split_in_chunks() {
+        let (_, mut contract) = setup();
+        let decimals = contract.ft_metadata().decimals;
+        assert_eq!(contract.split(2.into(), 1.into(), 0, 2), 2);
+        assert_eq!(contract.get_pending_split(), Some((U128(2), U128(1), 2)));
+        assert_eq!(contract.split(2.into(), 1.into(), 2, 2), 3);
+        assert_eq!(contract.get_pending_split(), None);
+
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 202);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 500);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, (TOTAL_SUPPLY - 351) * 2);
+        assert_eq!(contract.ft_total_supply().0, total_balance(&contract));
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY * 2);
+        assert_eq!(contract.ft_total_burned().0, 0);
+        assert_eq!(contract.ft_metadata().decimals, decimals);
+    }
+
+    #[test]
+    fn test_reverse_split_burns_dust() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.split(1.into(), 2.into(), 0, 10), 3);
+        assert_eq!(contract.get_pending_split(), None);
+
+        // bob and charlie had odd balances, each of them loses half a token.
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 50);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 125);
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, (TOTAL_SUPPLY - 352) / 2);
+        assert_eq!(contract.ft_total_supply().0, total_balance(&contract));
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY / 2 - 1);
+        assert_eq!(contract.ft_total_burned().0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Accounts can't be unregistered during a split")]
+    fn test_force_unregister_during_split() {
+        let (_, mut contract) = setup();
+        contract.split(2.into(), 1.into(), 0, 2);
+        contract.force_unregister(accounts(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "Accounts can't be unregistered during a split")]
+    fn test_storage_unregister_during_split() {
+        let (mut context, mut contract) = setup();
+        contract.split(2.into(), 1.into(), 0, 2);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.storage_unregister(Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "The split continues at index 2")]
+    fn test_split_skipped_chunk() {
+        let (_, mut contract) = setup();
+        contract.split(2.into(), 1.into(), 0, 2);
+        contract.split(2.into(), 1.into(), 3, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Another split is in progress")]
+    fn test_split_other_ratio() {
+        let (_, mut contract) = setup();
+        contract.split(2.into(), 1.into(), 0, 2);
+        contract.split(3.into(), 1.into(), 2, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The ratio denominator should be a positive number")]
+    fn test_split_zero_denominator() {
+        let (_, mut contract) = setup();
+        contract.split(2.into(), 0.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The ratio numerator should be a positive number")]
+    fn test_split_zero_numerator() {
+        let (_, mut contract) = setup();
+        contract.split(0.into(), 2.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The split would overflow the supply")]
+    fn test_split_overflow() {
+        let (_, mut contract) = setup();
+        contract.split(U128(u128::MAX / TOTAL_SUPPLY + 1), 1.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers should be paused during a split")]
+    fn test_split_not_paused() {
+        let (_, mut contract) = setup();
+        contract.unpause();
+        contract.split(2.into(), 1.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Transfers can't resume before the split is complete")]
+    fn test_unpause_during_split() {
+        let (_, mut contract) = setup();
+        contract.split(2.into(), 1.into(), 0, 2);
+        contract.unpause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapped NEAR can't be split")]
+    fn test_split_wrapped() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(accounts(2), 0.into(), test_metadata(), None, None, None, Some(true))
+        });
+        contract.pause();
+        contract.split(2.into(), 1.into(), 0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_split_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.split(2.into(), 1.into(), 0, 10);
+    }
+}
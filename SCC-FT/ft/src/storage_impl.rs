@@ -0,0 +1,831 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+use near_sdk::serde_json::json;
+use near_sdk::StorageUsage;
+
+/// The maximum number of accounts in a single `storage_deposit_batch` call, to keep it within
+/// the gas limit.
+const MAX_STORAGE_DEPOSIT_BATCH: usize = 100;
+/// The bytes charged for every storage record on top of its key and value.
+const STORAGE_RECORD_OVERHEAD: u64 = 40;
+/// The bytes of the longest possible account id serialized with its length.
+const MAX_ACCOUNT_ID_BYTES: u64 = 4 + 64;
+
+#[near_bindgen]
+impl StorageManagement for Contract {
+    /// Registers the account, charging the minimum storage balance. Unless `registration_only`
+    /// is set, the rest of the deposit is kept as available storage balance that can be
+    /// withdrawn with `storage_withdraw`, also when the account is already registered.
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let mut refund = amount;
+        if !self.token.accounts.contains_key(&account_id) {
+            let min_balance = self.storage_balance_bounds().min.0;
+            assert!(
+                amount >= min_balance,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            self.internal_register_paid_account(&account_id, min_balance);
+            let kept_deposit =
+                if registration_only.unwrap_or(false) { min_balance } else { amount };
+            self.on_account_registered(&account_id, kept_deposit);
+            refund -= min_balance;
+        }
+        if !registration_only.unwrap_or(false) && refund > 0 {
+            let available = self.storage_deposits.get(&account_id).unwrap_or(0);
+            self.storage_deposits.insert(&account_id, &(available + refund));
+            refund = 0;
+        }
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    /// Withdraws `amount` of the available storage balance of the caller, all of it by default.
+    /// The minimum storage balance stays locked until the account is unregistered.
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let storage_balance = self.storage_balance_of(account_id.clone()).unwrap_or_else(|| {
+            env::panic_str(&format!("The account {} is not registered", account_id))
+        });
+        let amount = amount.map_or(storage_balance.available.0, |amount| amount.0);
+        assert!(
+            amount <= storage_balance.available.0,
+            "The amount is greater than the available storage balance"
+        );
+        if amount > 0 {
+            self.internal_set_storage_deposit(&account_id, storage_balance.available.0 - amount);
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        self.storage_balance_of(account_id).unwrap()
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        let predecessor_id = env::predecessor_account_id();
+        self.assert_can_unregister(&predecessor_id);
+        self.internal_settle_reflection(&predecessor_id);
+        let registration_deposit = self.internal_registration_deposit(&predecessor_id);
+        // The token only releases the storage of the balance itself.
+        let token_refund =
+            Balance::from(self.token.account_storage_usage) * env::storage_byte_cost();
+        if let Some((account_id, balance)) = self.token.internal_storage_unregister(force) {
+            self.internal_update_holders(balance, 0);
+            self.internal_remove_account_data(&account_id);
+            self.total_burned += balance;
+            // The token already sent the released storage of the balance back to the account.
+            let overhead_refund = registration_deposit.saturating_sub(token_refund);
+            if overhead_refund > 0 {
+                Promise::new(account_id.clone()).transfer(overhead_refund);
+            }
+            self.on_account_closed(
+                account_id.clone(),
+                balance,
+                &account_id,
+                token_refund + overhead_refund,
+            );
+            self.internal_refund_storage_deposit(&account_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds { min: self.storage_cost_for_account(), max: None }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        if self.token.accounts.contains_key(&account_id) {
+            let available = self.storage_deposits.get(&account_id).unwrap_or(0);
+            Some(StorageBalance {
+                total: (self.internal_registration_deposit(&account_id) + available).into(),
+                available: available.into(),
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens like `ft_transfer`, first registering `receiver_id` with the attached
+    /// deposit if it isn't registered yet. Requires at least one yoctoNEAR attached, and at
+    /// least the minimum storage balance for an unregistered receiver. The deposit that isn't
+    /// used for the registration is refunded to the caller.
+    #[payable]
+    pub fn ft_transfer_register(
+        &mut self,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        let deposit = env::attached_deposit();
+        assert!(deposit >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let sender_id = env::predecessor_account_id();
+        let mut refund = deposit;
+        if !self.token.accounts.contains_key(&receiver_id) {
+            let min_balance = self.storage_balance_bounds().min.0;
+            assert!(
+                deposit >= min_balance,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            internal::assert_not_contract(&receiver_id);
+            self.internal_register_paid_account(&receiver_id, min_balance);
+            self.on_account_registered(&receiver_id, min_balance);
+            refund -= min_balance;
+        }
+        self.internal_ft_transfer(&sender_id, &receiver_id, amount.into(), memo);
+        if refund > 0 {
+            log!("Refunded {} yoctoNEAR of unused deposit to @{}", refund, sender_id);
+            Promise::new(sender_id).transfer(refund);
+        }
+    }
+
+    /// Registers every account in `account_ids` that isn't registered yet, charging the minimum
+    /// storage balance of each one to the attached deposit. Already registered accounts are
+    /// skipped. The deposit that isn't used for the registrations is refunded to the caller.
+    #[payable]
+    pub fn storage_deposit_batch(&mut self, account_ids: Vec<AccountId>) {
+        assert!(
+            account_ids.len() <= MAX_STORAGE_DEPOSIT_BATCH,
+            "The batch can't contain more than {} accounts",
+            MAX_STORAGE_DEPOSIT_BATCH
+        );
+        let mut new_accounts: Vec<&AccountId> = vec![];
+        for account_id in account_ids.iter() {
+            if !new_accounts.contains(&account_id) && !self.token.accounts.contains_key(account_id)
+            {
+                new_accounts.push(account_id);
+            }
+        }
+        let storage_cost = self.storage_balance_bounds().min.0 * new_accounts.len() as u128;
+        let deposit = env::attached_deposit();
+        assert!(
+            deposit >= storage_cost,
+            "The attached deposit should be at least {} to register {} accounts",
+            storage_cost,
+            new_accounts.len()
+        );
+        let min_balance = self.storage_balance_bounds().min.0;
+        for account_id in new_accounts {
+            self.internal_register_paid_account(account_id, min_balance);
+            self.on_account_registered(account_id, min_balance);
+        }
+        let refund = deposit - storage_cost;
+        if refund > 0 {
+            let sender_id = env::predecessor_account_id();
+            log!("Refunded {} yoctoNEAR of unused deposit to @{}", refund, sender_id);
+            Promise::new(sender_id).transfer(refund);
+        }
+    }
+
+    /// Returns `true` if the given account is registered and can receive tokens.
+    pub fn is_registered(&self, account_id: AccountId) -> bool {
+        self.token.accounts.contains_key(&account_id)
+    }
+
+    /// Returns the storage cost of registering an account, i.e. the bytes used per account
+    /// times the storage byte cost. This is what `storage_deposit` charges. Besides the balance,
+    /// the bytes include the worst case of the other data kept per account by the enabled
+    /// features.
+    pub fn storage_cost_for_account(&self) -> U128 {
+        (Balance::from(self.token.account_storage_usage) * env::storage_byte_cost()
+            + self.internal_account_overhead_cost())
+        .into()
+    }
+
+    /// Closes the given account, burning its remaining balance and the tokens of its vesting
+    /// grants that were not claimed yet. The minimum storage balance the account paid at its
+    /// registration is refunded to the owner, the available storage balance to the account. The
+    /// owner and the fee collector can't be unregistered. Can only be called by the owner.
+    pub fn force_unregister(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.assert_can_unregister(&account_id);
+        self.internal_settle_reflection(&account_id);
+        let balance = self.token.accounts.get(&account_id).unwrap_or_else(|| {
+            env::panic_str(&format!("The account {} is not registered", account_id))
+        });
+        if balance > 0 {
+            self.internal_burn(
+                &account_id,
+                balance.into(),
+                Some("Account force-unregistered".to_string()),
+            );
+        }
+        self.token.accounts.remove(&account_id);
+        let registration_deposit = self.internal_registration_deposit(&account_id);
+        self.internal_remove_account_data(&account_id);
+        let owner_id = self.owner_id.clone();
+        self.on_account_closed(account_id.clone(), balance, &owner_id, registration_deposit);
+        self.internal_refund_storage_deposit(&account_id);
+        Promise::new(owner_id).transfer(registration_deposit);
+    }
+}
+
+impl Contract {
+    /// Returns the storage cost of the data kept per account besides its balance: its entry in
+    /// the list of accounts, its registration deposit, its last activity and its idempotency
+    /// keys, its last sent transfer while the transfer cooldown is enabled and its reflection
+    /// checkpoint once reflections are enabled. The data an account pays for when storing it,
+    /// e.g. allowances, is not included.
+    fn internal_account_overhead_cost(&self) -> Balance {
+        // A map entry keyed by the account id behind a one byte prefix.
+        let account_entry = STORAGE_RECORD_OVERHEAD + 1 + MAX_ACCOUNT_ID_BYTES;
+        // The set of accounts keeps the index of every account behind a two byte prefix, and the
+        // account in a vector entry keyed by that index behind another two byte prefix.
+        let registered_account =
+            account_entry + 1 + 8 + STORAGE_RECORD_OVERHEAD + 2 + 8 + MAX_ACCOUNT_ID_BYTES;
+        let mut bytes = registered_account + account_entry + 16 + account_entry + 8;
+        // The idempotency keys remembered for the account as a sender.
+        bytes += account_entry + idempotency::MAX_IDEMPOTENCY_KEYS_BYTES;
+        if self.transfer_cooldown_seconds > 0 {
+            bytes += account_entry + 8;
+        }
+        if self.reflection_fee_bps > 0 || self.reflection_index > 0 {
+            bytes += account_entry + 16;
+        }
+        Balance::from(bytes) * env::storage_byte_cost()
+    }
+
+    /// Panics if `account_id` is the owner or the fee collector, which have to stay registered,
+    /// or while the registered accounts are processed in chunks. Closing an account moves the
+    /// last registered account to its index, which may already be processed.
+    fn assert_can_unregister(&self, account_id: &AccountId) {
+        assert!(account_id != &self.owner_id, "The owner can't be unregistered");
+        assert!(account_id != &self.fee_collector, "The fee collector can't be unregistered");
+        assert!(
+            self.pending_redenomination.is_none(),
+            "Accounts can't be unregistered during a redenomination"
+        );
+        assert!(self.pending_split.is_none(), "Accounts can't be unregistered during a split");
+        assert!(
+            self.pending_snapshot.is_none(),
+            "Accounts can't be unregistered during a snapshot"
+        );
+    }
+
+    /// Removes the data kept for a closed account besides its balance and storage balance. The
+    /// tokens of its vesting grants that were not claimed yet are burned. The permit nonce is
+    /// kept, so the signed permits of the account can't be replayed if it registers again.
+    fn internal_remove_account_data(&mut self, account_id: &AccountId) {
+        self.registered_accounts.remove(account_id);
+        self.reflection_checkpoints.remove(account_id);
+        self.last_activity.remove(account_id);
+        self.last_transfer_sent.remove(account_id);
+        self.large_transfer_approvals.remove(account_id);
+        self.allowed_receivers.remove(account_id);
+        self.emergency_recipients.remove(account_id);
+        self.daily_limits.remove(account_id);
+        self.spent_today.remove(account_id);
+        self.registration_deposits.remove(account_id);
+        self.idempotency_keys.remove(account_id);
+        self.permit_keys.remove(account_id);
+        self.labels.remove(account_id);
+        self.internal_remove_allowances(account_id);
+        self.internal_remove_vesting(account_id);
+    }
+
+    fn internal_set_storage_deposit(&mut self, account_id: &AccountId, available: Balance) {
+        if available > 0 {
+            self.storage_deposits.insert(account_id, &available);
+        } else {
+            self.storage_deposits.remove(account_id);
+        }
+    }
+
+    /// Refunds the available storage balance of a closed account.
+    fn internal_refund_storage_deposit(&mut self, account_id: &AccountId) {
+        if let Some(available) = self.storage_deposits.remove(account_id) {
+            log!(
+                "Refunded {} yoctoNEAR of available storage balance to @{}",
+                available,
+                account_id
+            );
+            Promise::new(account_id.clone()).transfer(available);
+        }
+    }
+
+    /// Charges the storage the call added since `initial_storage_usage` to the attached deposit
+    /// and refunds the rest of the deposit to the caller.
+    pub(crate) fn internal_charge_storage(&self, initial_storage_usage: StorageUsage) {
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let storage_cost = Balance::from(storage_used) * env::storage_byte_cost();
+        let deposit = env::attached_deposit();
+        scc_assert!(
+            deposit >= storage_cost,
+            SccError::InsufficientDeposit,
+            "The attached deposit should be at least {} yoctoNEAR to cover the storage",
+            storage_cost
+        );
+        let refund = deposit - storage_cost;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+    }
+
+    /// Returns the minimum storage balance `account_id` paid at its registration, which is what
+    /// closing it refunds. The deposits of the accounts registered before they were recorded
+    /// are assumed to be the current minimum storage balance.
+    fn internal_registration_deposit(&self, account_id: &AccountId) -> Balance {
+        self.registration_deposits
+            .get(account_id)
+            .unwrap_or_else(|| self.storage_cost_for_account().0)
+    }
+
+    /// Registers the account, recording the minimum storage balance it paid, so the same amount
+    /// is refunded when it is closed even if the storage bounds changed in the meantime.
+    pub(crate) fn internal_register_paid_account(
+        &mut self,
+        account_id: &AccountId,
+        registration_deposit: Balance,
+    ) {
+        self.internal_register_account(account_id);
+        self.registration_deposits.insert(account_id, &registration_deposit);
+    }
+
+    /// Registers the account in the token without charging for the storage.
+    pub(crate) fn internal_register_account(&mut self, account_id: &AccountId) {
+        self.token.internal_register_account(account_id);
+        self.registered_accounts.insert(account_id);
+    }
+
+    /// Emits an `account_registered` event for an account that was just registered with the
+    /// given storage deposit.
+    pub(crate) fn on_account_registered(&self, account_id: &AccountId, deposit: Balance) {
+        self.emit_event(
+            "account_registered",
+            &[json!({ "account_id": account_id, "deposit": U128(deposit) })],
+        );
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{testing_env, PublicKey};
+    use std::convert::TryFrom;
+
+    use super::*;
+    use crate::tests::{
+        get_context, init_contract, register_account, STORAGE_DEPOSIT, TOTAL_SUPPLY,
+    };
+
+    #[test]
+    fn test_unregister_logs_refund() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let registration_deposit = contract.storage_balance_bounds().min;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(registration_deposit.0)
+            .predecessor_account_id(accounts(1))
+            .build());
+        contract.storage_deposit(None, None);
+
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        assert!(contract.storage_unregister(None));
+        assert_eq!(
+            get_logs(),
+            vec![
+                format!("Closed @{} with 0", accounts(1)),
+                format!(
+                    "Refunded {} yoctoNEAR of released storage to @{}",
+                    registration_deposit.0,
+                    accounts(1)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unregister_refunds_paid_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let registration_deposit = contract.storage_balance_bounds().min.0;
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_transfer_cooldown(60);
+        assert!(contract.storage_balance_bounds().min.0 > registration_deposit);
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().total.0, registration_deposit);
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        assert!(contract.storage_unregister(None));
+        assert_eq!(
+            get_logs()[1],
+            format!(
+                "Refunded {} yoctoNEAR of released storage to @{}",
+                registration_deposit,
+                accounts(1)
+            )
+        );
+    }
+
+    #[test]
+    fn test_force_unregister_refunds_paid_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.set_transfer_cooldown(60);
+        let registration_deposit = contract.storage_balance_bounds().min.0;
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.set_transfer_cooldown(0);
+        assert!(contract.storage_balance_bounds().min.0 < registration_deposit);
+
+        contract.force_unregister(accounts(1));
+        assert_eq!(
+            get_logs().last().unwrap(),
+            &format!(
+                "Refunded {} yoctoNEAR of released storage to @{}",
+                registration_deposit,
+                accounts(2)
+            )
+        );
+    }
+
+    #[test]
+    fn test_force_unregister() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 10.into(), None);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.force_unregister(accounts(1));
+
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 10);
+        assert_eq!(contract.ft_total_burned().0, 10);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert!(contract.storage_balance_of(accounts(1)).is_none());
+        assert_eq!(contract.ft_holders_count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_force_unregister_not_owner() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        contract.force_unregister(accounts(2));
+    }
+
+    #[test]
+    fn test_storage_cost_for_account() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        assert_eq!(contract.storage_cost_for_account(), contract.storage_balance_bounds().min);
+        assert!(
+            contract.storage_cost_for_account().0
+                > Balance::from(contract.token.account_storage_usage) * env::storage_byte_cost()
+        );
+    }
+
+    #[test]
+    fn test_storage_bounds_include_enabled_features() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let base_min = contract.storage_balance_bounds().min.0;
+
+        contract.set_transfer_cooldown(60);
+        let cooldown_min = contract.storage_balance_bounds().min.0;
+        assert!(cooldown_min > base_min);
+
+        contract.set_reflection_fee(100);
+        assert!(contract.storage_balance_bounds().min.0 > cooldown_min);
+
+        contract.set_transfer_cooldown(0);
+        contract.set_reflection_fee(0);
+        assert_eq!(contract.storage_balance_bounds().min.0, base_min);
+    }
+
+    fn setup_over_deposit() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(min_balance + 1_000)
+            .predecessor_account_id(accounts(1))
+            .build());
+        let storage_balance = contract.storage_deposit(None, None);
+        assert_eq!(storage_balance.total.0, min_balance + 1_000);
+        assert_eq!(storage_balance.available.0, 1_000);
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_storage_withdraw_excess() {
+        let (_, mut contract) = setup_over_deposit();
+        let storage_balance = contract.storage_withdraw(Some(400.into()));
+        assert_eq!(storage_balance.available.0, 600);
+
+        let storage_balance = contract.storage_withdraw(None);
+        assert_eq!(storage_balance.available.0, 0);
+        assert_eq!(storage_balance.total, contract.storage_balance_bounds().min);
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount is greater than the available storage balance")]
+    fn test_storage_withdraw_locked_minimum() {
+        let (_, mut contract) = setup_over_deposit();
+        contract.storage_withdraw(Some(1_001.into()));
+    }
+
+    #[test]
+    fn test_account_registered_event() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(min_balance + 1_000)
+            .build());
+        contract.storage_deposit(None, None);
+        assert_eq!(
+            get_logs(),
+            vec![format!(
+                r#"EVENT_JSON:{{"standard":"scc","version":"1.0.0","event":"account_registered","data":[{{"account_id":"{}","deposit":"{}"}}]}}"#,
+                accounts(1),
+                min_balance + 1_000
+            )]
+        );
+
+        // A top-up of a registered account is not a registration.
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(1_000).build());
+        contract.storage_deposit(None, None);
+        assert!(get_logs().is_empty());
+        assert_eq!(contract.storage_balance_of(accounts(1)).unwrap().available.0, 2_000);
+    }
+
+    #[test]
+    fn test_storage_deposit_registration_only() {
+        let mut context = get_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(min_balance + 1_000)
+            .build());
+        let storage_balance = contract.storage_deposit(None, Some(true));
+        assert_eq!(storage_balance.total.0, min_balance);
+        assert_eq!(storage_balance.available.0, 0);
+    }
+
+    #[test]
+    fn test_is_registered() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        assert!(contract.is_registered(accounts(1)));
+        assert!(contract.is_registered(accounts(2)));
+        assert!(!contract.is_registered(accounts(3)));
+    }
+
+    #[test]
+    fn test_transfer_register_unregistered_receiver() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(min_balance + 500)
+            .build());
+        contract.ft_transfer_register(accounts(1), 10.into(), None);
+
+        assert!(contract.is_registered(accounts(1)));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+        assert_eq!(
+            get_logs().last().unwrap(),
+            &format!("Refunded 500 yoctoNEAR of unused deposit to @{}", accounts(2))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+    fn test_transfer_register_not_enough_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context.attached_deposit(min_balance - 1).build());
+        contract.ft_transfer_register(accounts(1), 10.into(), None);
+    }
+
+    #[test]
+    fn test_transfer_register_registered_receiver() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer_register(accounts(1), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 10);
+    }
+
+    #[test]
+    fn test_storage_deposit_batch() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(3 * min_balance + 5)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.storage_deposit_batch(vec![
+            accounts(1),
+            accounts(2),
+            accounts(3),
+            accounts(4),
+            accounts(1),
+        ]);
+
+        assert!(contract.is_registered(accounts(1)));
+        assert!(contract.is_registered(accounts(3)));
+        assert!(contract.is_registered(accounts(4)));
+        let mut expected_logs: Vec<String> = [accounts(1), accounts(3), accounts(4)]
+            .iter()
+            .map(|account_id| {
+                format!(
+                    r#"EVENT_JSON:{{"standard":"scc","version":"1.0.0","event":"account_registered","data":[{{"account_id":"{}","deposit":"{}"}}]}}"#,
+                    account_id, min_balance
+                )
+            })
+            .collect();
+        expected_logs.push("Refunded 5 yoctoNEAR of unused deposit to @charlie".to_string());
+        assert_eq!(get_logs(), expected_logs);
+    }
+
+    #[test]
+    #[should_panic(expected = "to register 3 accounts")]
+    fn test_storage_deposit_batch_not_enough_deposit() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        let min_balance = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(3 * min_balance - 1)
+            .build());
+        contract.storage_deposit_batch(vec![accounts(1), accounts(3), accounts(4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The owner can't be unregistered")]
+    fn test_force_unregister_owner() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.force_unregister(accounts(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "The fee collector can't be unregistered")]
+    fn test_force_unregister_fee_collector() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.internal_set_transfer_fee(100, accounts(1));
+        contract.force_unregister(accounts(1));
+    }
+
+    #[test]
+    fn test_force_unregister_burns_unclaimed_vesting() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.create_vesting(
+            accounts(1),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: 1_000,
+                end_timestamp: 3_000,
+                total: U128(1_000),
+                claimed: U128(0),
+            },
+        );
+        assert_eq!(contract.ft_total_locked().0, 1_000);
+
+        contract.force_unregister(accounts(1));
+        assert_eq!(contract.ft_total_locked().0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 0);
+        assert!(contract.get_vesting_grants(accounts(1)).is_empty());
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY - 1_000);
+        assert_eq!(contract.ft_total_burned().0, 1_000);
+    }
+
+    #[test]
+    fn test_force_unregister_removes_account_data() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(STORAGE_DEPOSIT)
+            .build());
+        contract.approve(accounts(3), 100.into(), None);
+        contract.set_permit_key(PublicKey::try_from(vec![0; 33]).unwrap());
+        testing_env!(context.attached_deposit(0).predecessor_account_id(accounts(2)).build());
+        contract.set_label(accounts(1), "Market maker".to_string());
+
+        contract.force_unregister(accounts(1));
+        assert_eq!(contract.allowance(accounts(1), accounts(3)).0, 0);
+        assert!(contract.allowance_spenders.get(&accounts(1)).is_none());
+        assert!(contract.permit_keys.get(&accounts(1)).is_none());
+        assert_eq!(contract.get_label(accounts(1)), None);
+    }
+}
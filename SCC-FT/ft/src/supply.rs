@@ -0,0 +1,177 @@
+use crate::events::SettingUpdate;
+use crate::*;
+use near_sdk::serde_json::json;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the total supply minus the balances of the excluded accounts.
+    pub fn ft_circulating_supply(&self) -> U128 {
+        let excluded_balance: Balance = self
+            .excluded_accounts
+            .iter()
+            .map(|account_id| self.internal_balance_of(&account_id))
+            .sum();
+        (self.token.total_supply - excluded_balance).into()
+    }
+
+    /// Returns the accounts excluded from the circulating supply.
+    pub fn get_excluded_accounts(&self) -> Vec<AccountId> {
+        self.excluded_accounts.to_vec()
+    }
+
+    /// Excludes the balance of the given account, e.g. a treasury, from the circulating supply.
+    /// Can only be called by the owner.
+    pub fn add_excluded_account(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.excluded_accounts.insert(&account_id),
+            "The account {} is already excluded",
+            account_id
+        );
+    }
+
+    /// Includes the balance of the given account back into the circulating supply.
+    /// Can only be called by the owner.
+    pub fn remove_excluded_account(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.excluded_accounts.remove(&account_id),
+            "The account {} is not excluded",
+            account_id
+        );
+    }
+
+    /// Returns the share of the max supply in basis points above which a `supply_warning` event
+    /// is emitted, if any.
+    pub fn get_supply_warning_bps(&self) -> Option<u16> {
+        self.supply_warning_bps
+    }
+
+    /// Sets the share of the max supply in basis points, e.g. 9000 for 90%, above which a
+    /// `supply_warning` event is emitted. The warning is emitted once when a mint pushes the
+    /// total supply above the threshold, and again only after the supply fell back below it.
+    /// `None` disables the warning. Can only be called by the owner.
+    pub fn set_supply_warning_bps(&mut self, bps: Option<u16>) {
+        self.assert_owner();
+        if let Some(bps) = bps {
+            assert!(
+                bps > 0 && bps <= 10_000,
+                "The supply warning threshold should be between 1 and 10000 basis points"
+            );
+        }
+        self.emit_admin_event(
+            "supply_warning_update",
+            SettingUpdate { account_id: None, old_value: self.supply_warning_bps, new_value: bps },
+        );
+        self.supply_warning_bps = bps;
+        self.supply_warning_emitted = self
+            .internal_supply_warning_threshold()
+            .map_or(false, |threshold| self.token.total_supply >= threshold);
+    }
+}
+
+impl Contract {
+    /// Returns the total supply above which the supply warning is emitted, if the warning is
+    /// enabled and there is a max supply.
+    fn internal_supply_warning_threshold(&self) -> Option<Balance> {
+        let max_supply = self.max_supply?;
+        self.supply_warning_bps.map(|bps| fee::apply_bps(max_supply, bps))
+    }
+
+    /// Emits a `supply_warning` event if the total supply just crossed the warning threshold,
+    /// and re-arms the warning once the total supply is below the threshold again.
+    pub(crate) fn internal_update_supply_warning(&mut self) {
+        let total_supply = self.token.total_supply;
+        let above_threshold = self
+            .internal_supply_warning_threshold()
+            .map_or(false, |threshold| total_supply >= threshold);
+        if above_threshold && !self.supply_warning_emitted {
+            self.emit_event(
+                "supply_warning",
+                &[json!({
+                    "total_supply": U128(total_supply),
+                    "max_supply": self.max_supply.map(U128),
+                    "warning_bps": self.supply_warning_bps,
+                })],
+            );
+        }
+        self.supply_warning_emitted = above_threshold;
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, get_logs};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, test_metadata, TOTAL_SUPPLY};
+
+    #[test]
+    fn test_circulating_supply() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        assert_eq!(contract.ft_circulating_supply().0, TOTAL_SUPPLY);
+
+        contract.add_excluded_account(accounts(2));
+        assert_eq!(contract.get_excluded_accounts(), vec![accounts(2)]);
+        assert_eq!(contract.ft_circulating_supply().0, 0);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        assert_eq!(contract.ft_circulating_supply().0, 100);
+
+        testing_env!(context.attached_deposit(0).build());
+        contract.add_excluded_account(accounts(1));
+        assert_eq!(contract.ft_circulating_supply().0, 0);
+
+        contract.remove_excluded_account(accounts(2));
+        assert_eq!(contract.ft_circulating_supply().0, TOTAL_SUPPLY - 100);
+    }
+
+    fn count_supply_warnings() -> usize {
+        get_logs().iter().filter(|log| log.contains("\"event\":\"supply_warning\"")).count()
+    }
+
+    #[test]
+    fn test_supply_warning_fires_once() {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(
+                accounts(2),
+                TOTAL_SUPPLY.into(),
+                test_metadata(),
+                Some((2 * TOTAL_SUPPLY).into()),
+                None,
+                None,
+                None,
+            )
+        });
+        contract.set_supply_warning_bps(Some(9_000));
+        assert_eq!(contract.get_supply_warning_bps(), Some(9_000));
+
+        testing_env!(context.build());
+        contract.mint(accounts(2), (TOTAL_SUPPLY / 2).into(), None);
+        assert_eq!(count_supply_warnings(), 0);
+        contract.mint(accounts(2), (TOTAL_SUPPLY * 4 / 10).into(), None);
+        assert_eq!(count_supply_warnings(), 1);
+        contract.mint(accounts(2), (TOTAL_SUPPLY / 20).into(), None);
+        assert_eq!(count_supply_warnings(), 1);
+
+        // Burning below the threshold re-arms the warning.
+        contract.burn((TOTAL_SUPPLY / 2).into(), None);
+        testing_env!(context.build());
+        contract.mint(accounts(2), (TOTAL_SUPPLY * 4 / 10).into(), None);
+        assert_eq!(count_supply_warnings(), 1);
+    }
+}
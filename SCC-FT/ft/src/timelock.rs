@@ -0,0 +1,211 @@
+use crate::*;
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The delay used by `new_default_meta` and `migrate` (1 day).
+pub(crate) const DEFAULT_TIMELOCK_DELAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+/// A sensitive owner action that can only be executed through the timelock.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TimelockAction {
+    SetTransferFee { bps: u16, collector: AccountId },
+    SetMaxSupply { max_supply: Option<U128> },
+}
+
+/// An action waiting in the timelock, executable once `ready_at` has passed.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QueuedAction {
+    pub payload: TimelockAction,
+    pub ready_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the delay in nanoseconds between queueing and executing an action.
+    pub fn get_timelock_delay(&self) -> U64 {
+        self.timelock_delay.into()
+    }
+
+    /// Returns the action queued under the given id, if any.
+    pub fn get_queued_action(&self, action_id: String) -> Option<QueuedAction> {
+        self.timelock_actions.get(&action_id)
+    }
+
+    /// Queues the given action under `action_id`. It can be executed once the timelock delay
+    /// has passed. Can only be called by the owner.
+    pub fn queue_action(&mut self, action_id: String, payload: TimelockAction) -> QueuedAction {
+        self.assert_owner();
+        assert!(
+            self.timelock_actions.get(&action_id).is_none(),
+            "The action {} is already queued",
+            action_id
+        );
+        self.assert_valid_action(&payload);
+        let queued_action = QueuedAction {
+            payload,
+            ready_at: (env::block_timestamp() + self.timelock_delay).into(),
+        };
+        self.timelock_actions.insert(&action_id, &queued_action);
+        log!("Action {} queued, ready at {}", action_id, queued_action.ready_at.0);
+        queued_action
+    }
+
+    /// Executes the action queued under `action_id` after its delay has passed.
+    /// Can only be called by the owner.
+    pub fn execute_action(&mut self, action_id: String) {
+        self.assert_owner();
+        let queued_action = self
+            .timelock_actions
+            .get(&action_id)
+            .unwrap_or_else(|| env::panic_str(&format!("The action {} is not queued", action_id)));
+        assert!(
+            env::block_timestamp() >= queued_action.ready_at.0,
+            "The action {} is not ready yet",
+            action_id
+        );
+        self.timelock_actions.remove(&action_id);
+        match queued_action.payload {
+            TimelockAction::SetTransferFee { bps, collector } => {
+                self.internal_set_transfer_fee(bps, collector)
+            }
+            TimelockAction::SetMaxSupply { max_supply } => {
+                self.internal_set_max_supply(max_supply.map(|max_supply| max_supply.0))
+            }
+        }
+        log!("Action {} executed", action_id);
+    }
+
+    /// Cancels the action queued under `action_id`. Can only be called by the owner.
+    pub fn cancel_action(&mut self, action_id: String) {
+        self.assert_owner();
+        assert!(
+            self.timelock_actions.remove(&action_id).is_some(),
+            "The action {} is not queued",
+            action_id
+        );
+        log!("Action {} cancelled", action_id);
+    }
+}
+
+impl Contract {
+    /// Checks the parts of the action that can't change while it waits in the timelock.
+    fn assert_valid_action(&self, payload: &TimelockAction) {
+        match payload {
+            TimelockAction::SetTransferFee { bps, .. } => fee::assert_valid_transfer_fee_bps(*bps),
+            TimelockAction::SetMaxSupply { .. } => {}
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .block_timestamp(0)
+            .build());
+        (context, contract)
+    }
+
+    fn set_fee_action() -> TimelockAction {
+        TimelockAction::SetTransferFee { bps: 250, collector: accounts(3) }
+    }
+
+    #[test]
+    fn test_execute_after_delay() {
+        let (mut context, mut contract) = setup();
+        let queued_action = contract.queue_action("fee".to_string(), set_fee_action());
+        assert_eq!(queued_action.ready_at.0, DEFAULT_TIMELOCK_DELAY);
+        assert_eq!(contract.get_queued_action("fee".to_string()), Some(queued_action));
+
+        testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY).build());
+        contract.execute_action("fee".to_string());
+        assert_eq!(contract.get_transfer_fee(), (250, accounts(3)));
+        assert_eq!(contract.get_queued_action("fee".to_string()), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The action fee is not ready yet")]
+    fn test_execute_too_early() {
+        let (mut context, mut contract) = setup();
+        contract.queue_action("fee".to_string(), set_fee_action());
+
+        testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY - 1).build());
+        contract.execute_action("fee".to_string());
+    }
+
+    #[test]
+    fn test_set_max_supply() {
+        let (mut context, mut contract) = setup();
+        // A token without a max supply can be capped, then the cap can be lowered.
+        for max_supply in [2 * TOTAL_SUPPLY, TOTAL_SUPPLY] {
+            let max_supply = Some(U128(max_supply));
+            testing_env!(context.block_timestamp(0).build());
+            contract
+                .queue_action("supply".to_string(), TimelockAction::SetMaxSupply { max_supply });
+            testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY).build());
+            contract.execute_action("supply".to_string());
+            assert_eq!(contract.get_max_supply(), max_supply);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "The max supply can only be lowered")]
+    fn test_raise_max_supply() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_max_supply(Some(TOTAL_SUPPLY));
+        let max_supply = Some(U128(TOTAL_SUPPLY + 1));
+        contract.queue_action("supply".to_string(), TimelockAction::SetMaxSupply { max_supply });
+
+        testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY).build());
+        contract.execute_action("supply".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The max supply can only be lowered")]
+    fn test_remove_max_supply() {
+        let (mut context, mut contract) = setup();
+        contract.internal_set_max_supply(Some(TOTAL_SUPPLY));
+        contract
+            .queue_action("supply".to_string(), TimelockAction::SetMaxSupply { max_supply: None });
+
+        testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY).build());
+        contract.execute_action("supply".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "The action fee is not queued")]
+    fn test_cancel_action() {
+        let (mut context, mut contract) = setup();
+        contract.queue_action("fee".to_string(), set_fee_action());
+        contract.cancel_action("fee".to_string());
+        assert_eq!(contract.get_queued_action("fee".to_string()), None);
+
+        testing_env!(context.block_timestamp(DEFAULT_TIMELOCK_DELAY).build());
+        contract.execute_action("fee".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the owner can call this method")]
+    fn test_queue_action_not_owner() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.queue_action("fee".to_string(), set_fee_action());
+    }
+}
@@ -0,0 +1,363 @@
+use crate::*;
+use near_contract_standards::fungible_token::events::FtTransfer;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// The maximum number of vesting grants of a single account, to keep claiming within the gas
+/// limit.
+const MAX_VESTING_GRANTS: u64 = 20;
+
+/// A vesting grant of an account. Nothing is vested before `cliff_timestamp`, then the
+/// tokens vest linearly until `end_timestamp`. Timestamps are in nanoseconds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingSchedule {
+    pub start_timestamp: u64,
+    pub cliff_timestamp: u64,
+    pub end_timestamp: u64,
+    pub total: U128,
+    pub claimed: U128,
+}
+
+impl VestingSchedule {
+    fn assert_valid(&self) {
+        assert!(
+            self.start_timestamp <= self.cliff_timestamp
+                && self.cliff_timestamp < self.end_timestamp,
+            "The vesting timestamps should satisfy start <= cliff < end"
+        );
+        assert!(self.total.0 > 0, "The vesting total should be positive");
+        assert_eq!(self.claimed.0, 0, "A new vesting schedule can't have claimed tokens");
+    }
+
+    /// Returns the amount vested at the given timestamp.
+    pub(crate) fn vested_amount(&self, timestamp: u64) -> Balance {
+        if timestamp < self.cliff_timestamp {
+            0
+        } else if timestamp >= self.end_timestamp {
+            self.total.0
+        } else {
+            mul_div(
+                self.total.0,
+                (timestamp - self.cliff_timestamp) as u128,
+                (self.end_timestamp - self.cliff_timestamp) as u128,
+            )
+        }
+    }
+
+    /// Returns the amount vested at the given timestamp that wasn't claimed yet.
+    pub(crate) fn claimable_amount(&self, timestamp: u64) -> Balance {
+        self.vested_amount(timestamp) - self.claimed.0
+    }
+}
+
+/// Returns `value * numerator / denominator` rounded down, for `numerator <= denominator`.
+fn mul_div(value: u128, numerator: u128, denominator: u128) -> u128 {
+    value / denominator * numerator + value % denominator * numerator / denominator
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the vesting grants of the given account in the order they were created.
+    pub fn get_vesting_grants(&self, account_id: AccountId) -> Vec<VestingSchedule> {
+        self.vesting.get(&account_id).map_or(vec![], |grants| grants.to_vec())
+    }
+
+    /// Returns the amount the given account can claim right now over all its grants.
+    pub fn get_claimable_vested(&self, account_id: AccountId) -> U128 {
+        let now = env::block_timestamp();
+        self.get_vesting_grants(account_id)
+            .iter()
+            .map(|schedule| schedule.claimable_amount(now))
+            .sum::<Balance>()
+            .into()
+    }
+
+    /// Returns the tokens of the vesting grants of the given account that are not vested yet.
+    /// They are held in escrow by the contract account, not in the balance of the account, so
+    /// `ft_balance_of` only reports the transferable balance and the locked tokens can never be
+    /// spent.
+    pub fn ft_balance_locked(&self, account_id: AccountId) -> U128 {
+        let now = env::block_timestamp();
+        self.get_vesting_grants(account_id)
+            .iter()
+            .map(|schedule| schedule.total.0 - schedule.vested_amount(now))
+            .sum::<Balance>()
+            .into()
+    }
+
+    /// Returns the tokens of all vesting grants that were not claimed yet, i.e. the tokens held
+    /// in escrow by the contract account. Vested tokens stay locked until they are claimed, so
+    /// this is at least the sum of `ft_balance_locked` over all accounts.
+    pub fn ft_total_locked(&self) -> U128 {
+        self.vesting_escrow_balance.into()
+    }
+
+    /// Locks `schedule.total` tokens of the owner in the contract account, to be released to
+    /// `account_id` according to the schedule. The grant is added to the other grants of the
+    /// account, up to 20 grants. Can only be called by the owner.
+    pub fn create_vesting(&mut self, account_id: AccountId, schedule: VestingSchedule) {
+        self.assert_owner();
+        schedule.assert_valid();
+        scc_assert!(
+            self.token.accounts.contains_key(&account_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            account_id
+        );
+        let mut grants = self.vesting.get(&account_id).unwrap_or_else(|| {
+            Vector::new(StorageKey::VestingGrants {
+                account_hash: env::sha256(account_id.as_bytes()),
+            })
+        });
+        assert!(
+            grants.len() < MAX_VESTING_GRANTS,
+            "The account {} can't have more than {} vesting grants",
+            account_id,
+            MAX_VESTING_GRANTS
+        );
+        let escrow_id = env::current_account_id();
+        if !self.token.accounts.contains_key(&escrow_id) {
+            self.internal_register_account(&escrow_id);
+        }
+        let owner_id = self.owner_id.clone();
+        self.internal_settle_reflection(&owner_id);
+        self.internal_withdraw(&self.owner_id, schedule.total.0);
+        self.internal_deposit(&escrow_id, schedule.total.0);
+        FtTransfer {
+            old_owner_id: &self.owner_id,
+            new_owner_id: &escrow_id,
+            amount: &schedule.total,
+            memo: Some("Vesting tokens locked"),
+        }
+        .emit();
+        self.vesting_escrow_balance += schedule.total.0;
+        grants.push(&schedule);
+        self.vesting.insert(&account_id, &grants);
+    }
+
+    /// Releases the vested but not yet claimed tokens of all the grants of the caller. Returns
+    /// the claimed amount.
+    pub fn claim_vested(&mut self) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut grants = self.vesting.get(&account_id).expect("No vesting schedule");
+        let now = env::block_timestamp();
+        let mut amount = 0;
+        for index in 0..grants.len() {
+            let mut schedule = grants.get(index).unwrap();
+            let claimable = schedule.claimable_amount(now);
+            if claimable > 0 {
+                schedule.claimed = U128(schedule.claimed.0 + claimable);
+                grants.replace(index, &schedule);
+                amount += claimable;
+            }
+        }
+        assert!(amount > 0, "Nothing to claim yet");
+        self.vesting_escrow_balance -= amount;
+
+        let escrow_id = env::current_account_id();
+        self.internal_settle_reflection(&account_id);
+        self.internal_withdraw(&escrow_id, amount);
+        self.internal_deposit(&account_id, amount);
+        FtTransfer {
+            old_owner_id: &escrow_id,
+            new_owner_id: &account_id,
+            amount: &U128(amount),
+            memo: Some("Vested tokens claimed"),
+        }
+        .emit();
+        amount.into()
+    }
+}
+
+impl Contract {
+    /// Removes the vesting grants of a closed account, burning the tokens that were not claimed
+    /// yet from the escrow.
+    pub(crate) fn internal_remove_vesting(&mut self, account_id: &AccountId) {
+        if let Some(mut grants) = self.vesting.remove(account_id) {
+            let unclaimed: Balance =
+                grants.iter().map(|schedule| schedule.total.0 - schedule.claimed.0).sum();
+            grants.clear();
+            if unclaimed > 0 {
+                self.vesting_escrow_balance -= unclaimed;
+                self.internal_burn(
+                    &env::current_account_id(),
+                    unclaimed.into(),
+                    Some("Vesting of a closed account".to_string()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    const CLIFF: u64 = 1_000;
+    const END: u64 = 3_000;
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(0)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.create_vesting(
+            accounts(1),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: CLIFF,
+                end_timestamp: END,
+                total: U128(1_000),
+                claimed: U128(0),
+            },
+        );
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_create_vesting_locks_tokens() {
+        let (_, contract) = setup();
+        assert_eq!(contract.ft_balance_of(accounts(2)).0, TOTAL_SUPPLY - 1_000);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 1_000);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY);
+        assert_eq!(contract.get_vesting_grants(accounts(1))[0].total, U128(1_000));
+    }
+
+    #[test]
+    fn test_claim_vested() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(CLIFF - 1).build());
+        assert_eq!(contract.get_claimable_vested(accounts(1)).0, 0);
+
+        testing_env!(context.block_timestamp(CLIFF + (END - CLIFF) / 4).build());
+        assert_eq!(contract.get_claimable_vested(accounts(1)).0, 250);
+        assert_eq!(contract.claim_vested().0, 250);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 250);
+
+        testing_env!(context.block_timestamp((CLIFF + END) / 2).build());
+        assert_eq!(contract.get_claimable_vested(accounts(1)).0, 250);
+
+        testing_env!(context.block_timestamp(END + 1).build());
+        assert_eq!(contract.claim_vested().0, 750);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_000);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 0);
+        assert_eq!(contract.get_vesting_grants(accounts(1))[0].claimed, U128(1_000));
+    }
+
+    #[test]
+    fn test_locked_and_transferable_balance() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(CLIFF - 1).build());
+        assert_eq!(contract.ft_balance_locked(accounts(1)).0, 1_000);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+
+        testing_env!(context.block_timestamp(CLIFF + (END - CLIFF) / 4).build());
+        assert_eq!(contract.ft_balance_locked(accounts(1)).0, 750);
+        contract.claim_vested();
+        assert_eq!(contract.ft_balance_locked(accounts(1)).0, 750);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 250);
+
+        testing_env!(context.block_timestamp(END).build());
+        assert_eq!(contract.ft_balance_locked(accounts(1)).0, 0);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 250);
+        assert_eq!(contract.ft_balance_locked(accounts(2)).0, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account doesn't have enough balance")]
+    fn test_transfer_locked_tokens() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(CLIFF + (END - CLIFF) / 4).build());
+        contract.claim_vested();
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(2), 251.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing to claim yet")]
+    fn test_claim_before_cliff() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.block_timestamp(CLIFF - 1).build());
+        contract.claim_vested();
+    }
+
+    #[test]
+    fn test_claim_overlapping_grants() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.create_vesting(
+            accounts(1),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: 2_000,
+                end_timestamp: 4_000,
+                total: U128(400),
+                claimed: U128(0),
+            },
+        );
+        assert_eq!(contract.get_vesting_grants(accounts(1)).len(), 2);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(2_500).build());
+        // 75% of the first grant and 25% of the second one.
+        assert_eq!(contract.get_claimable_vested(accounts(1)).0, 750 + 100);
+        assert_eq!(contract.ft_balance_locked(accounts(1)).0, 250 + 300);
+        assert_eq!(contract.claim_vested().0, 850);
+
+        testing_env!(context.block_timestamp(3_000).build());
+        assert_eq!(contract.get_claimable_vested(accounts(1)).0, 250 + 100);
+        assert_eq!(contract.claim_vested().0, 350);
+
+        let grants = contract.get_vesting_grants(accounts(1));
+        assert_eq!(grants[0].claimed, U128(1_000));
+        assert_eq!(grants[1].claimed, U128(200));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 1_200);
+        assert_eq!(contract.ft_balance_of(accounts(0)).0, 200);
+    }
+
+    #[test]
+    fn test_total_locked() {
+        let (mut context, mut contract) = setup();
+        assert_eq!(contract.ft_total_locked().0, 1_000);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.create_vesting(
+            accounts(2),
+            VestingSchedule {
+                start_timestamp: 0,
+                cliff_timestamp: 2_000,
+                end_timestamp: 4_000,
+                total: U128(400),
+                claimed: U128(0),
+            },
+        );
+        assert_eq!(contract.ft_total_locked().0, 1_400);
+
+        testing_env!(context.predecessor_account_id(accounts(1)).block_timestamp(2_500).build());
+        contract.claim_vested();
+        assert_eq!(contract.ft_total_locked().0, 1_400 - 750);
+
+        testing_env!(context.predecessor_account_id(accounts(2)).block_timestamp(4_000).build());
+        contract.claim_vested();
+        assert_eq!(contract.ft_total_locked().0, 250);
+        assert_eq!(contract.ft_total_locked(), contract.ft_balance_of(accounts(0)));
+    }
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(mul_div(u128::MAX, 1, 2), u128::MAX / 2);
+        assert_eq!(mul_div(u128::MAX, u64::MAX as u128, u64::MAX as u128), u128::MAX);
+        assert_eq!(mul_div(1_000, 1, 3), 333);
+    }
+}
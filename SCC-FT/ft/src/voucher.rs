@@ -0,0 +1,117 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+
+/// A promotional voucher redeemable once for `amount` newly minted tokens.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Voucher {
+    pub amount: U128,
+    pub redeemed: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the voucher with the given sha256 hash of its code, if any.
+    pub fn get_voucher(&self, code_hash: Base64VecU8) -> Option<Voucher> {
+        self.vouchers.get(&code_hash.0)
+    }
+
+    /// Creates a voucher redeemable for `amount` tokens by whoever knows the code whose sha256
+    /// hash is `code_hash`. Can only be called by the owner.
+    pub fn create_voucher(&mut self, code_hash: Base64VecU8, amount: U128) {
+        self.assert_owner();
+        self.assert_not_wrapped();
+        assert_eq!(code_hash.0.len(), 32, "The code hash should be 32 bytes");
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        assert!(
+            self.vouchers.insert(&code_hash.0, &Voucher { amount, redeemed: false }).is_none(),
+            "The voucher already exists"
+        );
+    }
+
+    /// Redeems the voucher with the given code, minting its tokens to the caller. An
+    /// unregistered caller is registered with the attached deposit, which has to cover the
+    /// minimum storage balance. The deposit that isn't used for the registration is refunded.
+    /// Returns the minted amount.
+    #[payable]
+    pub fn redeem_voucher(&mut self, code: String) -> U128 {
+        let code_hash = env::sha256(code.as_bytes());
+        let mut voucher = self.vouchers.get(&code_hash).expect("Invalid voucher code");
+        assert!(!voucher.redeemed, "The voucher was already redeemed");
+        let account_id = env::predecessor_account_id();
+        let mut refund = env::attached_deposit();
+        if !self.token.accounts.contains_key(&account_id) {
+            let min_balance = self.storage_balance_bounds().min.0;
+            assert!(
+                refund >= min_balance,
+                "The attached deposit is less than the minimum storage balance"
+            );
+            self.internal_register_paid_account(&account_id, min_balance);
+            self.on_account_registered(&account_id, min_balance);
+            refund -= min_balance;
+        }
+        voucher.redeemed = true;
+        self.vouchers.insert(&code_hash, &voucher);
+        self.internal_mint(&account_id, voucher.amount.0, Some("Voucher redeemed"));
+        if refund > 0 {
+            log!("Refunded {} yoctoNEAR of unused deposit to @{}", refund, account_id);
+            Promise::new(account_id).transfer(refund);
+        }
+        voucher.amount
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.create_voucher(Base64VecU8(env::sha256(b"WELCOME-42")), 500.into());
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(contract.storage_balance_bounds().min.0)
+            .predecessor_account_id(accounts(1))
+            .build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_redeem_voucher() {
+        let (_, mut contract) = setup();
+        assert_eq!(contract.redeem_voucher("WELCOME-42".to_string()).0, 500);
+
+        assert!(contract.is_registered(accounts(1)));
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 500);
+        assert_eq!(contract.ft_total_supply().0, TOTAL_SUPPLY + 500);
+        assert_eq!(
+            contract.get_voucher(Base64VecU8(env::sha256(b"WELCOME-42"))),
+            Some(Voucher { amount: U128(500), redeemed: true })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The voucher was already redeemed")]
+    fn test_redeem_voucher_twice() {
+        let (mut context, mut contract) = setup();
+        contract.redeem_voucher("WELCOME-42".to_string());
+        testing_env!(context.storage_usage(env::storage_usage()).attached_deposit(0).build());
+        contract.redeem_voucher("WELCOME-42".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid voucher code")]
+    fn test_redeem_invalid_voucher() {
+        let (_, mut contract) = setup();
+        contract.redeem_voucher("WELCOME-43".to_string());
+    }
+}
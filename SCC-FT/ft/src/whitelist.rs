@@ -0,0 +1,249 @@
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+/// The maximum number of receivers a sender can restrict its transfers to, to bound the storage
+/// used by the allowlist.
+const MAX_ALLOWED_RECEIVERS: usize = 20;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if only whitelisted accounts can currently transfer tokens.
+    pub fn is_transfer_whitelist_enabled(&self) -> bool {
+        self.transfer_whitelist_enabled
+    }
+
+    /// Returns `true` if the given account is on the transfer whitelist.
+    pub fn is_whitelisted(&self, account_id: AccountId) -> bool {
+        self.transfer_whitelist.contains(&account_id)
+    }
+
+    /// Returns the accounts on the transfer whitelist.
+    pub fn get_transfer_whitelist(&self) -> Vec<AccountId> {
+        self.transfer_whitelist.to_vec()
+    }
+
+    /// Enables or disables the transfer whitelist. While enabled, transfers only go through when
+    /// both the sender and the receiver are whitelisted. Can only be called by the owner.
+    pub fn set_transfer_whitelist_enabled(&mut self, enabled: bool) {
+        self.assert_owner();
+        self.transfer_whitelist_enabled = enabled;
+        log!("Transfer whitelist {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Adds the given account to the transfer whitelist. Can only be called by the owner.
+    pub fn add_to_whitelist(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.transfer_whitelist.insert(&account_id),
+            "The account {} is already whitelisted",
+            account_id
+        );
+        log!("Account @{} whitelisted", account_id);
+    }
+
+    /// Removes the given account from the transfer whitelist. Can only be called by the owner.
+    pub fn remove_from_whitelist(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        assert!(
+            self.transfer_whitelist.remove(&account_id),
+            "The account {} is not whitelisted",
+            account_id
+        );
+        log!("Account @{} removed from the whitelist", account_id);
+    }
+
+    /// Returns the receivers the given account restricted its transfers to. An empty list means
+    /// the account can transfer to anyone.
+    pub fn get_allowed_receivers(&self, account_id: AccountId) -> Vec<AccountId> {
+        self.allowed_receivers.get(&account_id).unwrap_or_default()
+    }
+
+    /// Restricts the transfers of the registered caller to the given receivers. Passing an empty
+    /// list removes the restriction. Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn set_allowed_receivers(&mut self, receiver_ids: Vec<AccountId>) {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        scc_assert!(
+            self.token.accounts.contains_key(&account_id),
+            SccError::NotRegistered,
+            "The account {} is not registered",
+            account_id
+        );
+        assert!(
+            receiver_ids.len() <= MAX_ALLOWED_RECEIVERS,
+            "The allowlist can't contain more than {} receivers",
+            MAX_ALLOWED_RECEIVERS
+        );
+        if receiver_ids.is_empty() {
+            self.allowed_receivers.remove(&account_id);
+            log!("Account @{} can transfer to any receiver", account_id);
+        } else {
+            log!(
+                "Account @{} restricted its transfers to {} receivers",
+                account_id,
+                receiver_ids.len()
+            );
+            self.allowed_receivers.insert(&account_id, &receiver_ids);
+        }
+    }
+}
+
+impl Contract {
+    /// Panics if the transfer whitelist is enabled and the transfer isn't allowed by it. The
+    /// owner can always send tokens and counts as whitelisted when receiving them.
+    pub(crate) fn assert_whitelisted(&self, sender_id: &AccountId, receiver_id: &AccountId) {
+        if !self.transfer_whitelist_enabled || sender_id == &self.owner_id {
+            return;
+        }
+        for account_id in [sender_id, receiver_id] {
+            scc_assert!(
+                account_id == &self.owner_id || self.transfer_whitelist.contains(account_id),
+                SccError::NotWhitelisted,
+                "The account {} is not whitelisted",
+                account_id
+            );
+        }
+    }
+
+    /// Panics if `sender_id` restricted its transfers to an allowlist that doesn't contain
+    /// `receiver_id`.
+    pub(crate) fn assert_allowed_receiver(&self, sender_id: &AccountId, receiver_id: &AccountId) {
+        if let Some(receiver_ids) = self.allowed_receivers.get(sender_id) {
+            scc_assert!(
+                receiver_ids.contains(receiver_id),
+                SccError::ReceiverNotAllowed,
+                "The account {} is not an allowed receiver of @{}",
+                receiver_id,
+                sender_id
+            );
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        register_account(&mut context, &mut contract, accounts(3));
+        testing_env!(context
+            .storage_usage(env::storage_usage())
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(2))
+            .build());
+        contract.ft_transfer(accounts(1), 100.into(), None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.set_transfer_whitelist_enabled(true);
+        (context, contract)
+    }
+
+    #[test]
+    fn test_whitelisted_transfer() {
+        let (mut context, mut contract) = setup();
+        contract.add_to_whitelist(accounts(1));
+        contract.add_to_whitelist(accounts(3));
+        assert!(contract.is_whitelisted(accounts(1)));
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account danny is not whitelisted")]
+    fn test_transfer_to_non_whitelisted() {
+        let (mut context, mut contract) = setup();
+        contract.add_to_whitelist(accounts(1));
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 10.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account bob is not whitelisted")]
+    fn test_transfer_call_from_non_whitelisted() {
+        let (mut context, mut contract) = setup();
+        contract.add_to_whitelist(accounts(3));
+
+        testing_env!(context
+            .attached_deposit(1)
+            .predecessor_account_id(accounts(1))
+            .prepaid_gas(Gas(300_000_000_000_000))
+            .build());
+        contract.ft_transfer_call(accounts(3), 10.into(), None, "".to_string());
+    }
+
+    #[test]
+    fn test_owner_always_allowed() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(1).build());
+        contract.ft_transfer(accounts(3), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 10);
+    }
+
+    #[test]
+    fn test_disable_restores_transfers() {
+        let (mut context, mut contract) = setup();
+        contract.set_transfer_whitelist_enabled(false);
+        assert!(!contract.is_transfer_whitelist_enabled());
+
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.ft_transfer(accounts(3), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 10);
+    }
+
+    #[test]
+    fn test_allowed_receiver() {
+        let (mut context, mut contract) = setup();
+        contract.set_transfer_whitelist_enabled(false);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.set_allowed_receivers(vec![accounts(3)]);
+        assert_eq!(contract.get_allowed_receivers(accounts(1)), vec![accounts(3)]);
+
+        contract.ft_transfer(accounts(3), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "The account charlie is not an allowed receiver of @bob")]
+    fn test_transfer_to_non_allowed_receiver() {
+        let (mut context, mut contract) = setup();
+        contract.set_transfer_whitelist_enabled(false);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.set_allowed_receivers(vec![accounts(3)]);
+
+        contract.ft_transfer(accounts(2), 10.into(), None);
+    }
+
+    #[test]
+    fn test_clear_allowed_receivers() {
+        let (mut context, mut contract) = setup();
+        contract.set_transfer_whitelist_enabled(false);
+        testing_env!(context.attached_deposit(1).predecessor_account_id(accounts(1)).build());
+        contract.set_allowed_receivers(vec![accounts(3)]);
+        contract.set_allowed_receivers(vec![]);
+        assert!(contract.get_allowed_receivers(accounts(1)).is_empty());
+
+        contract.ft_transfer(accounts(2), 10.into(), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 90);
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctoNEAR")]
+    fn test_set_allowed_receivers_requires_one_yocto() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.set_allowed_receivers(vec![accounts(3)]);
+    }
+}
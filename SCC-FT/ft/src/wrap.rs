@@ -0,0 +1,163 @@
+//! Wrapping of native NEAR for tokens configured as wrapped at initialization, which are backed
+//! 1:1 by the NEAR held by the contract. One NEAR (10^24 yoctoNEAR) is worth one whole token, so
+//! a token with 8 decimals mints one unit for every 10^16 yoctoNEAR.
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+/// The number of decimals of NEAR.
+const NEAR_DECIMALS: u8 = 24;
+
+#[near_bindgen]
+impl Contract {
+    /// Returns `true` if the token is backed 1:1 by NEAR and can be wrapped and unwrapped.
+    pub fn is_wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// Mints tokens worth the attached NEAR to the registered caller. The part of the deposit
+    /// that is too small to be worth a token unit is refunded. Returns the minted amount.
+    #[payable]
+    pub fn wrap(&mut self) -> U128 {
+        self.assert_wrapped();
+        let account_id = env::predecessor_account_id();
+        let unit = self.internal_near_per_unit();
+        let deposit = env::attached_deposit();
+        let amount = deposit / unit;
+        scc_assert!(
+            amount > 0,
+            SccError::InsufficientDeposit,
+            "The attached deposit should be at least {} yoctoNEAR",
+            unit
+        );
+        self.internal_mint(&account_id, amount, Some("Wrapped NEAR"));
+        let remainder = deposit % unit;
+        if remainder > 0 {
+            Promise::new(account_id).transfer(remainder);
+        }
+        amount.into()
+    }
+
+    /// Burns `amount` tokens of the caller and sends the NEAR backing them back to the caller.
+    /// Requires exactly one yoctoNEAR attached.
+    #[payable]
+    pub fn unwrap(&mut self, amount: U128) -> Promise {
+        assert_one_yocto();
+        self.assert_wrapped();
+        scc_assert!(amount.0 > 0, SccError::ZeroAmount, "The amount should be a positive number");
+        let account_id = env::predecessor_account_id();
+        self.internal_burn(&account_id, amount, Some("Unwrapped NEAR".to_string()));
+        Promise::new(account_id).transfer(amount.0 * self.internal_near_per_unit())
+    }
+}
+
+impl Contract {
+    fn assert_wrapped(&self) {
+        scc_assert!(self.wrapped, SccError::NotWrapped, "The token is not wrapped NEAR");
+    }
+
+    /// Panics if the token is wrapped NEAR, for the mints that aren't backed by NEAR, since their
+    /// tokens could be unwrapped for the NEAR backing the other tokens.
+    pub(crate) fn assert_not_wrapped(&self) {
+        scc_assert!(
+            !self.wrapped,
+            SccError::Wrapped,
+            "Wrapped NEAR can only be minted by wrapping NEAR"
+        );
+    }
+
+    /// Returns the amount of yoctoNEAR backing a single token unit.
+    fn internal_near_per_unit(&self) -> Balance {
+        10u128.pow((NEAR_DECIMALS - self.ft_decimals()) as u32)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+    use crate::tests::{get_context, init_contract, register_account, test_metadata, TOTAL_SUPPLY};
+
+    fn setup() -> (VMContextBuilder, Contract) {
+        let mut context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new(accounts(2), 0.into(), test_metadata(), None, None, None, Some(true))
+        });
+        register_account(&mut context, &mut contract, accounts(1));
+        testing_env!(context.storage_usage(env::storage_usage()).build());
+        (context, contract)
+    }
+
+    #[test]
+    fn test_wrap_unwrap() {
+        let (mut context, mut contract) = setup();
+        assert!(contract.is_wrapped());
+        testing_env!(context.attached_deposit(10u128.pow(24) + 1).build());
+        assert_eq!(contract.wrap().0, 100_000_000);
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 100_000_000);
+        assert_eq!(contract.ft_total_supply().0, 100_000_000);
+
+        testing_env!(context.attached_deposit(1).build());
+        contract.unwrap(40_000_000.into());
+        contract.unwrap(60_000_000.into());
+        assert_eq!(contract.ft_balance_of(accounts(1)).0, 0);
+        assert_eq!(contract.ft_total_supply().0, 0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The attached deposit should be at least 10000000000000000 yoctoNEAR"
+    )]
+    fn test_wrap_too_small_deposit() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.attached_deposit(10_000_000_000_000_000 - 1).build());
+        contract.wrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "The token is not wrapped NEAR")]
+    fn test_wrap_not_wrapped() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        let mut contract = init_contract(&context, || {
+            Contract::new_default_meta(accounts(2), TOTAL_SUPPLY.into())
+        });
+        contract.wrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapped NEAR can only be minted by wrapping NEAR")]
+    fn test_mint_wrapped() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.mint(accounts(1), 1.into(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapped NEAR can only be minted by wrapping NEAR")]
+    fn test_add_emission_wrapped() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.add_emission(1, 1.into(), accounts(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Wrapped NEAR can only be minted by wrapping NEAR")]
+    fn test_create_voucher_wrapped() {
+        let (mut context, mut contract) = setup();
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.create_voucher(vec![0; 32].into(), 1.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "A wrapped token should start with a zero total supply")]
+    fn test_wrapped_with_initial_supply() {
+        let context = get_context(accounts(2));
+        testing_env!(context.build());
+        init_contract(&context, || {
+            Contract::new(accounts(2), 1.into(), test_metadata(), None, None, None, Some(true))
+        });
+    }
+}
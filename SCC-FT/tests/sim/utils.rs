@@ -4,7 +4,8 @@ use fungible_token::ContractContract as FtContract;
 use near_sdk::json_types::U128;
 use near_sdk::serde_json::json;
 use near_sdk_sim::{
-    deploy, init_simulator, to_yocto, ContractAccount, UserAccount, DEFAULT_GAS, STORAGE_AMOUNT,
+    call, deploy, init_simulator, to_yocto, ContractAccount, UserAccount, DEFAULT_GAS,
+    STORAGE_AMOUNT,
 };
 
 // Load in contract bytes at runtime
@@ -70,13 +71,11 @@ pub fn init_with_macros(
         // Bytes of contract
         bytes: &FT_WASM_BYTES,
         // User deploying the contract,
-        signer_account: root,
-        // init method
-        init_method: new_default_meta(
-            root.account_id(),
-            initial_balance.into()
-        )
+        signer_account: root
     );
+    // The token can only be initialized by its own account
+    call!(ft.user_account, ft.new_default_meta(root.account_id(), initial_balance.into()))
+        .assert_success();
     let alice = root.create_user("alice".parse().unwrap(), to_yocto("100"));
     register_user(&alice);
 